@@ -0,0 +1,30 @@
+#!/usr/bin/env fish
+
+# Fills the `.ksyms` section of a kernel ELF with its function symbols,
+# so that backtraces can be symbolized at runtime (see `src/debug/symbols.rs`).
+
+# Must match `TABLE_SIZE` in `src/debug/symbols.rs`.
+set SECTION_SIZE 1048576
+
+set KERNEL $argv[1]
+set HOST (rustc -vV | string match -r 'host: (.*)')[2]
+set LLVM_BIN (rustc --print sysroot)/lib/rustlib/$HOST/bin
+set TABLE (mktemp)
+
+printf 'KSYMTAB\n' >$TABLE
+$LLVM_BIN/llvm-nm --defined-only --numeric-sort --print-size --demangle $KERNEL \
+    | awk '$3 ~ /^[tTwW]$/ { name = $4; for (i = 5; i <= NF; i++) name = name " " $i; print $1, $2, name }' \
+    | sed -E 's/^0+([0-9a-f])/\1/; s/ 0+([0-9a-f])/ \1/' >>$TABLE
+
+set TABLE_SIZE (stat -c %s $TABLE)
+if test $TABLE_SIZE -gt $SECTION_SIZE
+    echo "symbol table too large ($TABLE_SIZE > $SECTION_SIZE bytes)" >&2
+    rm $TABLE
+    exit 1
+end
+
+truncate -s $SECTION_SIZE $TABLE
+$LLVM_BIN/llvm-objcopy --update-section .ksyms=$TABLE $KERNEL
+set STATUS $status
+rm $TABLE
+exit $STATUS
@@ -0,0 +1,7 @@
+#!/usr/bin/env fish
+
+# Cargo runner: embeds the symbol table in the kernel, then boots it with bootimage.
+
+set SCRIPTS (path dirname (status filename))
+$SCRIPTS/embed-symbols.fish $argv[1]; or exit 1
+bootimage runner $argv
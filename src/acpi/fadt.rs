@@ -0,0 +1,165 @@
+// File: src/acpi/fadt.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use x86_64::PhysAddr;
+
+use super::{find_table, read_u16, read_u32, read_u64, table_at, GenericAddress};
+
+/// Offsets of the FADT fields, header included.
+mod offset {
+    pub const DSDT: usize = 40;
+    pub const SCI_INTERRUPT: usize = 46;
+    pub const SMI_COMMAND: usize = 48;
+    pub const ACPI_ENABLE: usize = 52;
+    pub const ACPI_DISABLE: usize = 53;
+    pub const PM1A_CONTROL: usize = 64;
+    pub const PM1B_CONTROL: usize = 68;
+    pub const PM_TIMER: usize = 76;
+    pub const PM1_CONTROL_LENGTH: usize = 89;
+    pub const PM_TIMER_LENGTH: usize = 91;
+    pub const BOOT_ARCHITECTURE: usize = 109;
+    pub const FLAGS: usize = 112;
+    pub const RESET_REGISTER: usize = 116;
+    pub const RESET_VALUE: usize = 128;
+    pub const X_DSDT: usize = 140;
+    pub const X_PM1A_CONTROL: usize = 172;
+    pub const X_PM1B_CONTROL: usize = 184;
+    pub const X_PM_TIMER: usize = 208;
+}
+
+/// The reset register is supported.
+const RESET_REGISTER_SUPPORTED: u32 = 1 << 10;
+/// An 8042 keyboard controller is present.
+const BOOT_ARCH_8042: u16 = 1 << 1;
+
+/// Fixed ACPI Description Table: the power management hardware.
+#[derive(Debug, Clone, Copy)]
+pub struct Fadt {
+    /// Physical address of the Differentiated System Description Table.
+    pub dsdt: u64,
+    /// Interrupt of the ACPI events.
+    pub sci_interrupt: u16,
+    /// Port of the system management command, 0 if ACPI is always enabled.
+    pub smi_command: u32,
+    /// Value to write to `smi_command` to enable ACPI.
+    pub acpi_enable: u8,
+    /// Value to write to `smi_command` to disable ACPI.
+    pub acpi_disable: u8,
+    /// `PM1a` control register, used to enter sleep states.
+    pub pm1a_control: Option<GenericAddress>,
+    /// `PM1b` control register.
+    pub pm1b_control: Option<GenericAddress>,
+    /// Power management timer.
+    pub pm_timer: Option<GenericAddress>,
+    /// Reset register and the value resetting the machine.
+    pub reset: Option<(GenericAddress, u8)>,
+    /// Whether an 8042 keyboard controller is present.
+    pub has_8042: bool,
+    /// Fixed feature flags.
+    pub flags: u32,
+}
+
+impl Fadt {
+    /// Parses the FADT, if the firmware provides one.
+    ///
+    /// The 64 bits fields of ACPI 2.0 are used when present.
+    #[must_use]
+    pub fn get() -> Option<Self> {
+        let (header, bytes) = find_table(b"FACP")?;
+        Self::parse(header.revision, bytes)
+    }
+
+    /// Parses the content of a FADT, header included.
+    ///
+    /// # Parameters
+    /// * `revision` - The revision of the table.
+    /// * `bytes` - The content of the table.
+    fn parse(revision: u8, bytes: &[u8]) -> Option<Self> {
+        let extended = revision >= 3;
+        let extended_field = |offset| {
+            extended
+                .then(|| GenericAddress::parse(bytes, offset))
+                .flatten()
+        };
+        let pm1_length = *bytes.get(offset::PM1_CONTROL_LENGTH)?;
+        let timer_length = *bytes.get(offset::PM_TIMER_LENGTH)?;
+        let flags = read_u32(bytes, offset::FLAGS)?;
+
+        Some(Self {
+            dsdt: extended
+                .then(|| read_u64(bytes, offset::X_DSDT))
+                .flatten()
+                .filter(|&addr| addr != 0)
+                .or_else(|| read_u32(bytes, offset::DSDT).map(u64::from))?,
+            sci_interrupt: read_u16(bytes, offset::SCI_INTERRUPT)?,
+            smi_command: read_u32(bytes, offset::SMI_COMMAND)?,
+            acpi_enable: *bytes.get(offset::ACPI_ENABLE)?,
+            acpi_disable: *bytes.get(offset::ACPI_DISABLE)?,
+            pm1a_control: extended_field(offset::X_PM1A_CONTROL).or_else(|| {
+                GenericAddress::io_port(read_u32(bytes, offset::PM1A_CONTROL)?, pm1_length)
+            }),
+            pm1b_control: extended_field(offset::X_PM1B_CONTROL).or_else(|| {
+                GenericAddress::io_port(read_u32(bytes, offset::PM1B_CONTROL)?, pm1_length)
+            }),
+            pm_timer: extended_field(offset::X_PM_TIMER).or_else(|| {
+                GenericAddress::io_port(read_u32(bytes, offset::PM_TIMER)?, timer_length)
+            }),
+            reset: (flags & RESET_REGISTER_SUPPORTED != 0)
+                .then(|| {
+                    Some((
+                        GenericAddress::parse(bytes, offset::RESET_REGISTER)?,
+                        *bytes.get(offset::RESET_VALUE)?,
+                    ))
+                })
+                .flatten(),
+            // ACPI 1.0 reserves the boot architecture flags and assumes a
+            // legacy PC.
+            has_8042: (revision < 2)
+                || read_u16(bytes, offset::BOOT_ARCHITECTURE)
+                    .is_none_or(|arch| arch & BOOT_ARCH_8042 != 0),
+            flags,
+        })
+    }
+
+    /// Content of the Differentiated System Description Table, header
+    /// included.
+    #[must_use]
+    pub fn dsdt_table(&self) -> Option<&'static [u8]> {
+        table_at(PhysAddr::new(self.dsdt)).map(|(_, bytes)| bytes)
+    }
+}
+
+/// ACPI 1.0 tables reserve the boot architecture flags: an 8042 is assumed.
+#[test_case]
+fn revision_1_assumes_an_8042() {
+    let bytes = [0; offset::RESET_REGISTER];
+    let legacy = Fadt::parse(1, &bytes).expect("revision 1 FADT not parsed");
+    assert!(legacy.has_8042, "no 8042 assumed on ACPI 1.0");
+    let modern = Fadt::parse(2, &bytes).expect("revision 2 FADT not parsed");
+    assert!(!modern.has_8042, "8042 reported without its flag");
+}
@@ -0,0 +1,66 @@
+// File: src/acpi/hpet.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use super::{find_table, read_u16, GenericAddress};
+
+/// High Precision Event Timer table.
+#[derive(Debug, Clone, Copy)]
+pub struct Hpet {
+    /// Location of the timer registers.
+    pub base_address: GenericAddress,
+    /// Sequence number of the timer.
+    pub number: u8,
+    /// Minimum period of the periodic mode, in main counter ticks.
+    pub minimum_tick: u16,
+    /// Number of comparators.
+    pub comparators: u8,
+    /// Whether the main counter is 64 bits wide.
+    pub is_64_bits: bool,
+    /// Whether the timer can replace the legacy PIT and RTC interrupts.
+    pub legacy_replacement: bool,
+    /// PCI vendor identifier of the timer.
+    pub vendor_id: u16,
+}
+
+impl Hpet {
+    /// Parses the HPET table, if the firmware provides one.
+    #[must_use]
+    pub fn get() -> Option<Self> {
+        let (_, bytes) = find_table(b"HPET")?;
+        let capabilities = read_u16(bytes, 36)? >> 8;
+        Some(Self {
+            base_address: GenericAddress::parse(bytes, 40)?,
+            number: *bytes.get(52)?,
+            minimum_tick: read_u16(bytes, 53)?,
+            comparators: (capabilities & 0x1F) as u8 + 1,
+            is_64_bits: capabilities & (1 << 5) != 0,
+            legacy_replacement: capabilities & (1 << 7) != 0,
+            vendor_id: read_u16(bytes, 38)?,
+        })
+    }
+}
@@ -0,0 +1,154 @@
+// File: src/acpi/madt.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use super::{find_table, read_u16, read_u32, read_u64, SdtHeader};
+
+/// Entry type of a processor local APIC.
+const LOCAL_APIC: u8 = 0;
+/// Entry type of an I/O APIC.
+const IO_APIC: u8 = 1;
+/// Entry type of an interrupt source override.
+const INTERRUPT_OVERRIDE: u8 = 2;
+/// Entry type of a 64 bits local APIC address override.
+const LOCAL_APIC_ADDRESS_OVERRIDE: u8 = 5;
+/// The processor is usable.
+const PROCESSOR_ENABLED: u32 = 1;
+/// The processor can be enabled at runtime.
+const PROCESSOR_ONLINE_CAPABLE: u32 = 1 << 1;
+
+/// A processor, as described in the MADT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Processor {
+    /// ACPI processor identifier.
+    pub processor_id: u8,
+    /// Identifier of the processor local APIC.
+    pub apic_id: u8,
+    /// Whether the processor can be started.
+    pub usable: bool,
+}
+
+/// An I/O APIC, routing the external interrupts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoApic {
+    /// Identifier of the I/O APIC.
+    pub id: u8,
+    /// Physical address of its registers.
+    pub address: u32,
+    /// First global system interrupt it handles.
+    pub interrupt_base: u32,
+}
+
+/// An ISA interrupt not identity mapped to a global system interrupt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InterruptOverride {
+    /// Source ISA interrupt.
+    pub source: u8,
+    /// Global system interrupt it is delivered on.
+    pub global_interrupt: u32,
+    /// Polarity and trigger mode (MPS INTI flags).
+    pub flags: u16,
+}
+
+/// Multiple APIC Description Table: the interrupt controllers.
+#[derive(Debug, Clone)]
+pub struct Madt {
+    /// Physical address of the local APICs.
+    pub local_apic_address: u64,
+    /// Whether the legacy 8259 PICs are also present.
+    pub has_legacy_pics: bool,
+    /// Every processor of the machine.
+    pub processors: Vec<Processor>,
+    /// Every I/O APIC of the machine.
+    pub io_apics: Vec<IoApic>,
+    /// Remapped ISA interrupts.
+    pub overrides: Vec<InterruptOverride>,
+}
+
+impl Madt {
+    /// Parses the MADT, if the firmware provides one.
+    #[must_use]
+    pub fn get() -> Option<Self> {
+        let (_, bytes) = find_table(b"APIC")?;
+        let fields = bytes.get(size_of::<SdtHeader>()..)?;
+        let mut madt = Self {
+            local_apic_address: u64::from(read_u32(fields, 0)?),
+            has_legacy_pics: read_u32(fields, 4)? & 1 != 0,
+            processors: Vec::new(),
+            io_apics: Vec::new(),
+            overrides: Vec::new(),
+        };
+
+        let mut entries = fields.get(8..)?;
+        while let [kind, length, ..] = *entries {
+            let length = usize::from(length).max(2);
+            let entry = entries.get(..length)?;
+            match kind {
+                LOCAL_APIC => {
+                    let [_, _, processor_id, apic_id, ..] = *entry else {
+                        return None;
+                    };
+                    let flags = read_u32(entry, 4)?;
+                    madt.processors.push(Processor {
+                        processor_id,
+                        apic_id,
+                        usable: flags & (PROCESSOR_ENABLED | PROCESSOR_ONLINE_CAPABLE) != 0,
+                    });
+                }
+                IO_APIC => {
+                    let [_, _, id, ..] = *entry else {
+                        return None;
+                    };
+                    madt.io_apics.push(IoApic {
+                        id,
+                        address: read_u32(entry, 4)?,
+                        interrupt_base: read_u32(entry, 8)?,
+                    });
+                }
+                INTERRUPT_OVERRIDE => {
+                    let [_, _, _, source, ..] = *entry else {
+                        return None;
+                    };
+                    madt.overrides.push(InterruptOverride {
+                        source,
+                        global_interrupt: read_u32(entry, 4)?,
+                        flags: read_u16(entry, 8)?,
+                    });
+                }
+                LOCAL_APIC_ADDRESS_OVERRIDE => {
+                    madt.local_apic_address = read_u64(entry, 4)?;
+                }
+                _ => {}
+            }
+            entries = entries.get(length..)?;
+        }
+        Some(madt)
+    }
+}
@@ -0,0 +1,101 @@
+// File: src/acpi/mcfg.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use x86_64::PhysAddr;
+
+use super::{find_table, read_u16, read_u64, SdtHeader};
+
+/// Size of an entry of the table.
+const ENTRY_SIZE: usize = 16;
+
+/// Enhanced configuration access region of a PCI segment group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcamRegion {
+    /// Physical address of the configuration space of `start_bus`.
+    pub base_address: u64,
+    /// PCI segment group.
+    pub segment_group: u16,
+    /// First bus of the region.
+    pub start_bus: u8,
+    /// Last bus of the region.
+    pub end_bus: u8,
+}
+
+impl EcamRegion {
+    /// Physical address of the configuration space of a function.
+    ///
+    /// Returns `None` if the function is outside of the region.
+    ///
+    /// # Parameters
+    /// * `bus` - The bus of the device,
+    /// * `device` - The device, below 32,
+    /// * `function` - The function, below 8.
+    #[must_use]
+    pub fn config_address(&self, bus: u8, device: u8, function: u8) -> Option<PhysAddr> {
+        if !(self.start_bus..=self.end_bus).contains(&bus) || device >= 32 || function >= 8 {
+            return None;
+        }
+        let offset = u64::from(bus - self.start_bus) << 20
+            | u64::from(device) << 15
+            | u64::from(function) << 12;
+        Some(PhysAddr::new(self.base_address + offset))
+    }
+}
+
+/// PCI Express memory mapped configuration table.
+#[derive(Debug, Clone)]
+pub struct Mcfg {
+    /// Configuration regions, one per segment group.
+    pub regions: Vec<EcamRegion>,
+}
+
+impl Mcfg {
+    /// Parses the MCFG table, if the firmware provides one.
+    #[must_use]
+    pub fn get() -> Option<Self> {
+        let (_, bytes) = find_table(b"MCFG")?;
+        let (entries, _) = bytes
+            .get(size_of::<SdtHeader>() + 8..)?
+            .as_chunks::<ENTRY_SIZE>();
+        let regions = entries
+            .iter()
+            .map(|entry| {
+                Some(EcamRegion {
+                    base_address: read_u64(entry, 0)?,
+                    segment_group: read_u16(entry, 8)?,
+                    start_bus: entry[10],
+                    end_bus: entry[11],
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Self { regions })
+    }
+}
@@ -0,0 +1,319 @@
+// File: src/acpi/mod.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{mem::size_of, ptr, slice};
+
+use conquer_once::spin::OnceCell;
+use x86_64::PhysAddr;
+
+use crate::paging::phys_to_virt;
+
+pub use fadt::Fadt;
+pub use hpet::Hpet;
+pub use madt::{InterruptOverride, IoApic, Madt, Processor};
+pub use mcfg::{EcamRegion, Mcfg};
+
+/// Fixed ACPI Description Table.
+mod fadt;
+/// High Precision Event Timer table.
+mod hpet;
+/// Multiple APIC Description Table.
+mod madt;
+/// PCI Express memory mapped configuration table.
+mod mcfg;
+
+/// Signature of the Root System Description Pointer.
+const RSDP_SIGNATURE: &[u8; 8] = b"RSD PTR ";
+/// Physical address of the segment of the Extended BIOS Data Area.
+const EBDA_SEGMENT: u64 = 0x40E;
+/// BIOS read-only memory, where the RSDP can also be.
+const BIOS_AREA: (u64, u64) = (0xE_0000, 0x10_0000);
+
+/// Physical address of the root table, and whether it is an XSDT.
+static ROOT_TABLE: OnceCell<Option<(PhysAddr, bool)>> = OnceCell::uninit();
+
+/// Root System Description Pointer (ACPI 2.0 layout).
+#[derive(Debug, Clone, Copy)]
+#[repr(C, packed)]
+struct Rsdp {
+    signature: [u8; 8],
+    checksum: u8,
+    oem_id: [u8; 6],
+    revision: u8,
+    rsdt_address: u32,
+    length: u32,
+    xsdt_address: u64,
+    extended_checksum: u8,
+    reserved: [u8; 3],
+}
+
+/// Size of the ACPI 1.0 part of the RSDP.
+const RSDP_V1_SIZE: usize = 20;
+
+/// Header common to every System Description Table.
+#[derive(Debug, Clone, Copy)]
+#[repr(C, packed)]
+pub struct SdtHeader {
+    /// Table signature (`APIC`, `FACP`…).
+    pub signature: [u8; 4],
+    /// Length of the table, header included.
+    pub length: u32,
+    /// Revision of the table.
+    pub revision: u8,
+    /// Checksum of the whole table.
+    pub checksum: u8,
+    /// OEM identifier.
+    pub oem_id: [u8; 6],
+    /// OEM table identifier.
+    pub oem_table_id: [u8; 8],
+    /// OEM revision.
+    pub oem_revision: u32,
+    /// Vendor of the tool that created the table.
+    pub creator_id: u32,
+    /// Revision of the tool that created the table.
+    pub creator_revision: u32,
+}
+
+/// Address space of a [`GenericAddress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSpace {
+    /// Physical memory.
+    SystemMemory,
+    /// I/O ports.
+    SystemIo,
+    /// PCI configuration space.
+    PciConfiguration,
+    /// Any other address space.
+    Other(u8),
+}
+
+/// Generic Address Structure: the location of a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenericAddress {
+    /// Address space of the register.
+    pub address_space: AddressSpace,
+    /// Size of the register, in bits.
+    pub bit_width: u8,
+    /// Offset of the register in the address, in bits.
+    pub bit_offset: u8,
+    /// Access size (1 byte to 4 for 8 bytes, 0 if undefined).
+    pub access_size: u8,
+    /// Address of the register.
+    pub address: u64,
+}
+
+impl GenericAddress {
+    /// Size of the structure in a table.
+    const SIZE: usize = 12;
+
+    /// Parses the structure at the given offset of a table.
+    ///
+    /// Returns `None` for a null address, which marks an absent register.
+    fn parse(bytes: &[u8], offset: usize) -> Option<Self> {
+        let &[space, bit_width, bit_offset, access_size, ..] =
+            bytes.get(offset..offset.checked_add(Self::SIZE)?)?
+        else {
+            return None;
+        };
+        let address = read_u64(bytes, offset + 4)?;
+        (address != 0).then_some(Self {
+            address_space: match space {
+                0 => AddressSpace::SystemMemory,
+                1 => AddressSpace::SystemIo,
+                2 => AddressSpace::PciConfiguration,
+                other => AddressSpace::Other(other),
+            },
+            bit_width,
+            bit_offset,
+            access_size,
+            address,
+        })
+    }
+
+    /// I/O register of the ACPI 1.0 fields.
+    ///
+    /// # Parameters
+    /// * `port` - The port of the register, 0 if absent,
+    /// * `length` - Its length, in bytes.
+    fn io_port(port: u32, length: u8) -> Option<Self> {
+        (port != 0).then_some(Self {
+            address_space: AddressSpace::SystemIo,
+            bit_width: length.saturating_mul(8),
+            bit_offset: 0,
+            access_size: 0,
+            address: u64::from(port),
+        })
+    }
+}
+
+/// Reads a structure from physical memory.
+///
+/// # Parameters
+/// * `addr` - Physical address of the structure.
+///
+/// # Safety
+/// The memory must hold a valid `T`.
+unsafe fn read_phys<T: Copy>(addr: PhysAddr) -> Option<T> {
+    let virt = phys_to_virt(addr)?;
+    // SAFETY:
+    // Guaranteed by the caller, the memory is mapped.
+    Some(unsafe { ptr::read_unaligned(virt.as_ptr::<T>()) })
+}
+
+/// Bytes of physical memory.
+///
+/// # Parameters
+/// * `addr` - Physical address of the bytes,
+/// * `len` - Number of bytes.
+fn phys_bytes(addr: PhysAddr, len: usize) -> Option<&'static [u8]> {
+    let virt = phys_to_virt(addr)?;
+    // SAFETY:
+    // The complete physical memory is mapped, and ACPI tables are never
+    // modified.
+    Some(unsafe { slice::from_raw_parts(virt.as_ptr(), len) })
+}
+
+/// Reads a little endian number from a table.
+///
+/// # Parameters
+/// * `bytes` - Content of the table,
+/// * `offset` - Offset of the number,
+/// * `size` - Size of the number, in bytes.
+fn read_le(bytes: &[u8], offset: usize, size: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset.checked_add(size)?)?;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |value, &byte| value << 8 | u64::from(byte)),
+    )
+}
+
+/// Reads a 16 bits field of a table.
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    read_le(bytes, offset, 2).and_then(|value| u16::try_from(value).ok())
+}
+
+/// Reads a 32 bits field of a table.
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    read_le(bytes, offset, 4).and_then(|value| u32::try_from(value).ok())
+}
+
+/// Reads a 64 bits field of a table.
+fn read_u64(bytes: &[u8], offset: usize) -> Option<u64> {
+    read_le(bytes, offset, 8)
+}
+
+/// Whether the bytes sum to zero, as required for every ACPI structure.
+fn is_checksum_valid(bytes: &[u8]) -> bool {
+    bytes.iter().fold(0_u8, |sum, &byte| sum.wrapping_add(byte)) == 0
+}
+
+/// Looks for the RSDP on the 16 bytes boundaries of a memory area.
+fn search_rsdp(start: u64, end: u64) -> Option<PhysAddr> {
+    (start..end).step_by(16).map(PhysAddr::new).find(|&addr| {
+        phys_bytes(addr, RSDP_V1_SIZE)
+            .is_some_and(|bytes| bytes.starts_with(RSDP_SIGNATURE) && is_checksum_valid(bytes))
+    })
+}
+
+/// Finds the root table, through the RSDP in the BIOS memory.
+fn find_root_table() -> Option<(PhysAddr, bool)> {
+    // SAFETY:
+    // The BIOS data area holds the segment of the EBDA.
+    let ebda = u64::from(unsafe { read_phys::<u16>(PhysAddr::new(EBDA_SEGMENT))? }) << 4;
+    let rsdp_addr =
+        search_rsdp(ebda, ebda + 1024).or_else(|| search_rsdp(BIOS_AREA.0, BIOS_AREA.1))?;
+    // SAFETY:
+    // The RSDP was found at this address.
+    let rsdp = unsafe { read_phys::<Rsdp>(rsdp_addr)? };
+    if rsdp.revision >= 2 {
+        let length = usize::try_from(rsdp.length).ok()?;
+        if phys_bytes(rsdp_addr, length).is_some_and(is_checksum_valid) {
+            return Some((PhysAddr::new(rsdp.xsdt_address), true));
+        }
+    }
+    Some((PhysAddr::new(u64::from(rsdp.rsdt_address)), false))
+}
+
+/// Reads and validates the table at the given address.
+///
+/// Returns its header and its content, header included.
+fn table_at(addr: PhysAddr) -> Option<(SdtHeader, &'static [u8])> {
+    // SAFETY:
+    // Tables addresses come from the firmware.
+    let header = unsafe { read_phys::<SdtHeader>(addr)? };
+    let length = usize::try_from(header.length).ok()?;
+    let bytes = phys_bytes(addr, length)?;
+    (length >= size_of::<SdtHeader>() && is_checksum_valid(bytes)).then_some((header, bytes))
+}
+
+/// Every valid table listed by the RSDT or the XSDT.
+///
+/// Yields their headers and their contents, headers included.
+pub fn tables() -> impl Iterator<Item = (SdtHeader, &'static [u8])> {
+    let root = (*ROOT_TABLE.get_or_init(find_root_table))
+        .and_then(|(addr, extended)| Some((table_at(addr)?.1, extended)));
+    let (bytes, extended) = root.unwrap_or((&[], false));
+    let entry_size = if extended { 8 } else { 4 };
+    bytes
+        .get(size_of::<SdtHeader>()..)
+        .unwrap_or_default()
+        .chunks_exact(entry_size)
+        .filter_map(move |entry| read_le(entry, 0, entry_size))
+        .filter_map(|addr| table_at(PhysAddr::new(addr)))
+}
+
+/// Finds the table with the given signature.
+///
+/// Returns its header and its content, header included.
+///
+/// # Parameters
+/// * `signature` - The signature of the table.
+#[must_use]
+pub fn find_table(signature: &[u8; 4]) -> Option<(SdtHeader, &'static [u8])> {
+    tables().find(|(header, _)| &header.signature == signature)
+}
+
+/// The firmware provides ACPI tables.
+#[test_case]
+fn root_table_is_found() {
+    assert!(tables().next().is_some(), "no ACPI table found");
+}
+
+/// Every table listed by QEMU is parsed.
+#[test_case]
+fn tables_are_parsed() {
+    let madt = Madt::get().expect("no MADT");
+    assert!(!madt.processors.is_empty(), "no processor in the MADT");
+    assert!(!madt.io_apics.is_empty(), "no I/O APIC in the MADT");
+    let fadt = Fadt::get().expect("no FADT");
+    assert!(fadt.pm1a_control.is_some(), "no PM1a control register");
+    let hpet = Hpet::get().expect("no HPET table");
+    assert_eq!(hpet.base_address.address_space, AddressSpace::SystemMemory);
+}
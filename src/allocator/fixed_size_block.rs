@@ -33,6 +33,7 @@ use core::{
 };
 
 use linked_list_allocator::Heap;
+use x86_64::instructions::interrupts;
 
 use super::lock::Locked;
 
@@ -91,46 +92,53 @@ fn list_index(layout: &Layout) -> Option<usize> {
 unsafe impl GlobalAlloc for Locked<FixedSizeBlockAllocator> {
     #[expect(clippy::unwrap_used)]
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let mut allocator = self.lock();
-        match list_index(&layout) {
-            Some(index) => {
-                if let Some(node) = allocator.list_heads[index].take() {
-                    allocator.list_heads[index] = node.next.take();
-                    from_mut::<ListNode>(node).cast::<u8>()
-                } else {
-                    // no block exists in list => allocate new block
-                    let block_size = BLOCK_SIZES[index];
-                    // only works if all block sizes are a power of 2
-                    let block_align = block_size;
-                    let actual_layout = Layout::from_size_align(block_size, block_align).unwrap();
-                    allocator.fallback_alloc(actual_layout)
+        // the lock must not be held by a preempted thread
+        interrupts::without_interrupts(|| {
+            let mut allocator = self.lock();
+            match list_index(&layout) {
+                Some(index) => {
+                    if let Some(node) = allocator.list_heads[index].take() {
+                        allocator.list_heads[index] = node.next.take();
+                        from_mut::<ListNode>(node).cast::<u8>()
+                    } else {
+                        // no block exists in list => allocate new block
+                        let block_size = BLOCK_SIZES[index];
+                        // only works if all block sizes are a power of 2
+                        let block_align = block_size;
+                        let actual_layout =
+                            Layout::from_size_align(block_size, block_align).unwrap();
+                        allocator.fallback_alloc(actual_layout)
+                    }
                 }
+                None => allocator.fallback_alloc(layout),
             }
-            None => allocator.fallback_alloc(layout),
-        }
+        })
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        let mut allocator = self.lock();
-        if let Some(index) = list_index(&layout) {
-            let new_node = ListNode {
-                next: allocator.list_heads[index].take(),
-            };
+        // the lock must not be held by a preempted thread
+        interrupts::without_interrupts(|| {
+            let mut allocator = self.lock();
+            if let Some(index) = list_index(&layout) {
+                let new_node = ListNode {
+                    next: allocator.list_heads[index].take(),
+                };
 
-            // verify that block has size and alignment required for storing node
-            assert!(mem::size_of::<ListNode>() <= BLOCK_SIZES[index]);
-            assert!(mem::align_of::<ListNode>() <= BLOCK_SIZES[index]);
+                // verify that block has size and alignment required for storing node
+                assert!(mem::size_of::<ListNode>() <= BLOCK_SIZES[index]);
+                assert!(mem::align_of::<ListNode>() <= BLOCK_SIZES[index]);
 
-            #[expect(clippy::cast_ptr_alignment)]
-            let new_node_ptr = ptr.cast::<ListNode>();
-            unsafe {
-                new_node_ptr.write(new_node);
+                #[expect(clippy::cast_ptr_alignment)]
+                let new_node_ptr = ptr.cast::<ListNode>();
+                unsafe {
+                    new_node_ptr.write(new_node);
+                }
+                allocator.list_heads[index] = Some(&mut *new_node_ptr);
+            } else {
+                #[expect(clippy::unwrap_used)]
+                let ptr = NonNull::new(ptr).unwrap();
+                allocator.fallback_allocator.deallocate(ptr, layout);
             }
-            allocator.list_heads[index] = Some(&mut *new_node_ptr);
-        } else {
-            #[expect(clippy::unwrap_used)]
-            let ptr = NonNull::new(ptr).unwrap();
-            allocator.fallback_allocator.deallocate(ptr, layout);
-        }
+        });
     }
 }
@@ -1,16 +1,19 @@
-/// A wrapper around `spin::Mutex` to permit trait implementations.
+use crate::spinlock::{IrqSpinlock, IrqSpinlockGuard};
+
+/// A wrapper around [`IrqSpinlock`] to permit trait implementations.
 pub struct Locked<A> {
-    inner: spin::Mutex<A>,
+    inner: IrqSpinlock<A>,
 }
 
 impl<A> Locked<A> {
     pub const fn new(inner: A) -> Self {
         Self {
-            inner: spin::Mutex::new(inner),
+            inner: IrqSpinlock::new(inner),
         }
     }
 
-    pub fn lock(&self) -> spin::MutexGuard<A> {
+    #[track_caller]
+    pub fn lock(&self) -> IrqSpinlockGuard<A> {
         self.inner.lock()
     }
 }
@@ -0,0 +1,232 @@
+// File: src/debug/backtrace.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{arch::asm, fmt, iter};
+
+use x86_64::VirtAddr;
+
+use super::symbols::{self, Symbol};
+use crate::{emergency_println, paging::translate_addr};
+
+/// Maximum number of frames walked.
+const MAX_FRAMES: usize = 64;
+
+/// A frame of the call stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Return address of the frame (or faulting address for the first frame
+    /// of an exception).
+    pub address: u64,
+    /// The function the address belongs to, if known.
+    pub symbol: Option<Symbol>,
+}
+
+impl Frame {
+    fn new(address: u64) -> Self {
+        Self {
+            address,
+            symbol: symbols::resolve(address),
+        }
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.symbol {
+            Some(symbol) => write!(
+                f,
+                "{:#018x}: {}+{:#x}",
+                self.address,
+                symbol.name,
+                self.address - symbol.address
+            ),
+            None => write!(f, "{:#018x}: <unknown>", self.address),
+        }
+    }
+}
+
+/// Iterator over the return addresses of the call stack, following the
+/// frame pointers.
+///
+/// The kernel is compiled with frame pointers: each frame starts with the
+/// frame pointer of its caller, followed by the return address.
+struct FrameWalker {
+    /// Frame pointer of the next frame to read.
+    frame_pointer: u64,
+}
+
+impl FrameWalker {
+    /// Whether a frame pointer can be dereferenced.
+    ///
+    /// Both the saved frame pointer and the return address must be mapped: the
+    /// stack may be corrupted, and faulting here would turn a panic or an
+    /// exception report into a double fault.
+    fn is_valid(frame_pointer: u64) -> bool {
+        let is_mapped = |address| {
+            VirtAddr::try_new(address).is_ok_and(|address| translate_addr(address).is_some())
+        };
+        frame_pointer != 0
+            && frame_pointer % 8 == 0
+            && is_mapped(frame_pointer)
+            && frame_pointer.checked_add(8).is_some_and(is_mapped)
+    }
+
+    /// Reads the frame pointer of the caller of a frame.
+    ///
+    /// # Parameters
+    /// * `frame_pointer` - The frame to read.
+    fn caller(frame_pointer: u64) -> Option<u64> {
+        // SAFETY:
+        // The frame pointer was validated, and frame pointers are always
+        // pushed by the function prologues.
+        Self::is_valid(frame_pointer).then(|| unsafe { *(frame_pointer as *const u64) })
+    }
+}
+
+impl Iterator for FrameWalker {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let caller = Self::caller(self.frame_pointer)?;
+        // SAFETY:
+        // Validated by `caller`, the return address follows the frame pointer.
+        let return_address = unsafe { *(self.frame_pointer as *const u64).add(1) };
+        // the stack grows downwards: callers are always higher up
+        self.frame_pointer = if caller > self.frame_pointer {
+            caller
+        } else {
+            0
+        };
+        (return_address != 0).then_some(return_address)
+    }
+}
+
+/// Current value of the frame pointer.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+#[expect(clippy::inline_always, reason = "must read the frame of its caller")]
+#[inline(always)]
+fn frame_pointer() -> u64 {
+    let rbp: u64;
+    // SAFETY:
+    // Only reads a register.
+    unsafe {
+        asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+    }
+    rbp
+}
+
+/// A captured call stack.
+///
+/// Frames are copied when captured, and the capture does not allocate so it
+/// can be used from panic and exception handlers.
+pub struct Backtrace {
+    /// Addresses of the frames, innermost first.
+    addresses: [u64; MAX_FRAMES],
+    /// Number of valid entries in `addresses`.
+    len: usize,
+}
+
+impl Backtrace {
+    fn from_addresses(addresses: impl Iterator<Item = u64>) -> Self {
+        let mut backtrace = Self {
+            addresses: [0; MAX_FRAMES],
+            len: 0,
+        };
+        for (slot, address) in backtrace.addresses.iter_mut().zip(addresses) {
+            *slot = address;
+            backtrace.len += 1;
+        }
+        backtrace
+    }
+
+    /// Captures the call stack of the caller.
+    #[inline(never)]
+    #[must_use]
+    pub fn capture() -> Self {
+        Self::from_addresses(FrameWalker {
+            frame_pointer: frame_pointer(),
+        })
+    }
+
+    /// Captures the call stack of the code interrupted by an exception.
+    ///
+    /// Must be called directly from the exception handler: the frame pointer
+    /// saved by the handler is the one of the interrupted code, which may be
+    /// running on another stack.
+    ///
+    /// # Parameters
+    /// * `instruction_pointer` - Address of the faulting instruction.
+    #[inline(never)]
+    #[must_use]
+    pub fn capture_exception(instruction_pointer: VirtAddr) -> Self {
+        let interrupted_frame = FrameWalker::caller(frame_pointer())
+            .and_then(FrameWalker::caller)
+            .unwrap_or(0);
+        Self::from_addresses(iter::once(instruction_pointer.as_u64()).chain(FrameWalker {
+            frame_pointer: interrupted_frame,
+        }))
+    }
+
+    /// Frames of the backtrace, innermost first.
+    pub fn frames(&self) -> impl Iterator<Item = Frame> + '_ {
+        self.addresses[..self.len].iter().copied().map(Frame::new)
+    }
+
+    /// Prints the backtrace to the screen and the serial port.
+    ///
+    /// Uses the emergency output: backtraces are printed by the panic and
+    /// fault handlers.
+    pub fn print(&self) {
+        emergency_println!("Backtrace:");
+        for (index, frame) in self.frames().enumerate() {
+            emergency_println!("  #{index:<2} {frame}");
+        }
+    }
+}
+
+/// Frame pointers to unmapped memory are not followed.
+#[test_case]
+fn unmapped_frames_are_not_walked() {
+    let mut walker = FrameWalker {
+        frame_pointer: 0x7FFF_FFFF_0000,
+    };
+    assert_eq!(walker.next(), None);
+}
+
+/// A frame pointing to itself ends the walk instead of looping.
+#[test_case]
+fn frame_cycles_end_the_walk() {
+    use core::cell::Cell;
+
+    let frame = [Cell::new(0_u64), Cell::new(0x1234)];
+    let frame_pointer = frame.as_ptr() as u64;
+    frame[0].set(frame_pointer);
+    let mut walker = FrameWalker { frame_pointer };
+    assert_eq!(walker.next(), Some(0x1234));
+    assert_eq!(walker.next(), None);
+}
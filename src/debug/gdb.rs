@@ -0,0 +1,655 @@
+// File: src/debug/gdb.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use uart_16550::SerialPort;
+use x86_64::{
+    instructions::interrupts::int3,
+    registers::control::{Cr0, Cr0Flags},
+    VirtAddr,
+};
+
+use crate::{interrupts::trap::TrapFrame, paging::translate_addr, spinlock::IrqSpinlock};
+
+/// Port address of the second serial interface.
+const SERIAL_PORT: u16 = 0x2F8;
+/// Maximum size of a packet, as advertised to the debugger.
+const PACKET_SIZE: usize = 1024;
+/// Maximum number of software breakpoints.
+const MAX_BREAKPOINTS: usize = 32;
+/// The `int3` instruction.
+const INT3: u8 = 0xCC;
+/// Number of 64 bits registers in the `g` packet (`rax` to `rip`).
+const WIDE_REGISTERS: usize = 17;
+/// Maximum number of bytes read by a single `m` packet, each byte taking two
+/// hexadecimal digits in the reply.
+const MAX_MEMORY_READ: u64 = 512;
+/// Number of 32 bits registers in the `g` packet (`eflags` to `gs`).
+const NARROW_REGISTERS: usize = 7;
+
+/// Whether the stub handles breakpoints and debug exceptions.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Serial port the debugger is connected to.
+static PORT: IrqSpinlock<Option<SerialPort>> = IrqSpinlock::new(None);
+/// Software breakpoints: address and the original byte.
+static BREAKPOINTS: IrqSpinlock<[Option<(u64, u8)>; MAX_BREAKPOINTS]> =
+    IrqSpinlock::new([None; MAX_BREAKPOINTS]);
+
+/// Signals reported to the debugger when the kernel stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Signal {
+    /// Stopped on a breakpoint or after a single step (`SIGTRAP`).
+    Trap = 5,
+}
+
+/// Initializes the second serial port and enables the stub.
+///
+/// From then on, breakpoints (`int3`) and debug exceptions stop the kernel
+/// and hand control to the debugger, which can be attached with
+/// `target remote` on the host side of the serial port.
+pub fn init() {
+    // SAFETY:
+    // Port-Mapping IO
+    let mut port = unsafe { SerialPort::new(SERIAL_PORT) };
+    port.init();
+    *PORT.lock() = Some(port);
+    ENABLED.store(true, Ordering::Release);
+}
+
+/// Whether the stub is enabled.
+#[must_use]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Stops the kernel and waits for the debugger.
+pub fn breakpoint() {
+    int3();
+}
+
+/// Hands control to the debugger after a breakpoint or debug exception.
+///
+/// Returns once the debugger resumes execution.
+///
+/// # Parameters
+/// * `frame` - Registers of the interrupted code, updated by the debugger,
+/// * `signal` - Reason of the stop.
+pub(crate) fn handle_trap(frame: &mut TrapFrame, signal: Signal) {
+    // a trap on one of our breakpoints: report the address of the breakpoint
+    let breakpoint = frame.rip.wrapping_sub(1);
+    if BREAKPOINTS
+        .lock()
+        .iter()
+        .flatten()
+        .any(|&(address, _)| address == breakpoint)
+    {
+        frame.rip = breakpoint;
+    }
+    frame.set_single_step(false);
+
+    // not locked during the session, which lasts as long as the debugger wants
+    let Some(mut port) = PORT.lock().take() else {
+        return;
+    };
+    let mut session = Session {
+        port: &mut port,
+        frame,
+        signal,
+    };
+    session.run();
+    *PORT.lock() = Some(port);
+}
+
+/// A stop of the kernel, during which the debugger is in control.
+struct Session<'a> {
+    port: &'a mut SerialPort,
+    frame: &'a mut TrapFrame,
+    signal: Signal,
+}
+
+impl Session<'_> {
+    /// Serves the debugger requests until it resumes execution.
+    fn run(&mut self) {
+        self.send_stop_reply();
+        let mut packet = [0; PACKET_SIZE];
+        let mut reply = Reply::new();
+        loop {
+            let len = self.receive_packet(&mut packet);
+            reply.clear();
+            let resume = self.handle(&packet[..len], &mut reply);
+            self.send_packet(reply.as_bytes());
+            if resume {
+                return;
+            }
+        }
+    }
+
+    /// Handles a single packet.
+    ///
+    /// Returns whether execution should resume.
+    ///
+    /// # Parameters
+    /// * `packet` - The packet payload,
+    /// * `reply` - Buffer for the reply payload.
+    fn handle(&mut self, packet: &[u8], reply: &mut Reply) -> bool {
+        let Some((&command, args)) = packet.split_first() else {
+            return false;
+        };
+        match command {
+            b'?' => reply.stop(self.signal),
+            b'g' => self.read_registers(reply),
+            b'G' => reply.status(self.write_registers(args)),
+            b'p' => {
+                if !self.read_register(args, reply) {
+                    reply.error();
+                }
+            }
+            b'P' => reply.status(self.write_register(args)),
+            b'm' => {
+                if !read_memory(args, reply) {
+                    reply.clear();
+                    reply.error();
+                }
+            }
+            b'M' => reply.status(write_memory(args)),
+            b'Z' => reply.status(set_breakpoint(args, true)),
+            b'z' => reply.status(set_breakpoint(args, false)),
+            b'c' => {
+                self.resume(args, false);
+                return true;
+            }
+            b's' => {
+                self.resume(args, true);
+                return true;
+            }
+            b'D' | b'k' => {
+                reply.ok();
+                return true;
+            }
+            b'q' if args.starts_with(b"Supported") => {
+                reply.push_str(b"PacketSize=");
+                reply.push_hex(PACKET_SIZE as u64);
+            }
+            b'q' if args.starts_with(b"Attached") => reply.push_str(b"1"),
+            b'q' if args.starts_with(b"C") => reply.push_str(b"QC1"),
+            b'H' => reply.ok(),
+            _ => {}
+        }
+        false
+    }
+
+    /// Sets the resume address and trap flag before returning from the trap.
+    ///
+    /// # Parameters
+    /// * `args` - Optional resume address,
+    /// * `single_step` - Whether to stop after the next instruction.
+    fn resume(&mut self, args: &[u8], single_step: bool) {
+        if let Some(address) = parse_hex(args) {
+            self.frame.rip = address;
+        }
+        self.frame.set_single_step(single_step);
+    }
+
+    /// Registers, in the order expected by the debugger for `amd64`.
+    const fn wide_registers(&self) -> [u64; WIDE_REGISTERS] {
+        let frame = &self.frame;
+        [
+            frame.rax, frame.rbx, frame.rcx, frame.rdx, frame.rsi, frame.rdi, frame.rbp, frame.rsp,
+            frame.r8, frame.r9, frame.r10, frame.r11, frame.r12, frame.r13, frame.r14, frame.r15,
+            frame.rip,
+        ]
+    }
+
+    /// Mutable references to the registers, in the order of
+    /// [`Self::wide_registers`] then `eflags`, `cs` and `ss`.
+    const fn registers_mut(&mut self) -> [&mut u64; WIDE_REGISTERS + 3] {
+        let frame = &mut *self.frame;
+        [
+            &mut frame.rax,
+            &mut frame.rbx,
+            &mut frame.rcx,
+            &mut frame.rdx,
+            &mut frame.rsi,
+            &mut frame.rdi,
+            &mut frame.rbp,
+            &mut frame.rsp,
+            &mut frame.r8,
+            &mut frame.r9,
+            &mut frame.r10,
+            &mut frame.r11,
+            &mut frame.r12,
+            &mut frame.r13,
+            &mut frame.r14,
+            &mut frame.r15,
+            &mut frame.rip,
+            &mut frame.rflags,
+            &mut frame.cs,
+            &mut frame.ss,
+        ]
+    }
+
+    /// The 32 bits registers: `eflags`, `cs`, `ss`, `ds`, `es`, `fs`, `gs`.
+    const fn narrow_registers(&self) -> [u64; NARROW_REGISTERS] {
+        [self.frame.rflags, self.frame.cs, self.frame.ss, 0, 0, 0, 0]
+    }
+
+    fn read_registers(&self, reply: &mut Reply) {
+        for register in self.wide_registers() {
+            reply.push_le(register, 8);
+        }
+        for register in self.narrow_registers() {
+            reply.push_le(register, 4);
+        }
+    }
+
+    fn write_registers(&mut self, args: &[u8]) -> bool {
+        let (chunks, _) = args.as_chunks::<16>();
+        let mut chunks = chunks.iter();
+        for register in self.registers_mut().into_iter().take(WIDE_REGISTERS) {
+            match chunks.next().and_then(|chunk| parse_le(chunk)) {
+                Some(value) => *register = value,
+                None => return false,
+            }
+        }
+        // eflags is 32 bits wide
+        if let Some(flags) = args.get(WIDE_REGISTERS * 16..WIDE_REGISTERS * 16 + 8) {
+            if let Some(flags) = parse_le(flags) {
+                self.frame.rflags = flags;
+            }
+        }
+        true
+    }
+
+    fn read_register(&self, args: &[u8], reply: &mut Reply) -> bool {
+        let Some(index) = parse_hex(args).and_then(|index| usize::try_from(index).ok()) else {
+            return false;
+        };
+        if let Some(&value) = self.wide_registers().get(index) {
+            reply.push_le(value, 8);
+        } else if let Some(&value) = self.narrow_registers().get(index - WIDE_REGISTERS) {
+            reply.push_le(value, 4);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn write_register(&mut self, args: &[u8]) -> bool {
+        let Some((index, value)) = split_once(args, b'=') else {
+            return false;
+        };
+        let Some(index) = parse_hex(index).and_then(|index| usize::try_from(index).ok()) else {
+            return false;
+        };
+        let Some(value) = parse_le(value) else {
+            return false;
+        };
+        // segment registers other than cs/ss cannot be changed
+        self.registers_mut().into_iter().nth(index).map_or(
+            index < WIDE_REGISTERS + NARROW_REGISTERS,
+            |register| {
+                *register = value;
+                true
+            },
+        )
+    }
+
+    fn send_stop_reply(&mut self) {
+        let mut reply = Reply::new();
+        reply.stop(self.signal);
+        self.send_packet(reply.as_bytes());
+    }
+
+    /// Waits for a valid packet, acknowledging it.
+    ///
+    /// Returns the length of the payload written in `buffer`.
+    ///
+    /// # Parameters
+    /// * `buffer` - Buffer receiving the payload.
+    fn receive_packet(&mut self, buffer: &mut [u8; PACKET_SIZE]) -> usize {
+        loop {
+            while self.port.receive() != b'$' {}
+
+            let mut len = 0;
+            let mut checksum: u8 = 0;
+            loop {
+                let byte = self.port.receive();
+                if byte == b'#' {
+                    break;
+                }
+                checksum = checksum.wrapping_add(byte);
+                if let Some(slot) = buffer.get_mut(len) {
+                    *slot = byte;
+                    len += 1;
+                }
+            }
+            let expected = [self.port.receive(), self.port.receive()];
+            if parse_hex(&expected) == Some(u64::from(checksum)) {
+                self.port.send_raw(b'+');
+                return len;
+            }
+            self.port.send_raw(b'-');
+        }
+    }
+
+    /// Sends a packet, until the debugger acknowledges it.
+    ///
+    /// # Parameters
+    /// * `payload` - The packet payload.
+    fn send_packet(&mut self, payload: &[u8]) {
+        loop {
+            self.port.send_raw(b'$');
+            let mut checksum: u8 = 0;
+            for &byte in payload {
+                checksum = checksum.wrapping_add(byte);
+                self.port.send_raw(byte);
+            }
+            self.port.send_raw(b'#');
+            let [high, low] = hex_byte(checksum);
+            self.port.send_raw(high);
+            self.port.send_raw(low);
+            if self.port.receive() == b'+' {
+                return;
+            }
+        }
+    }
+}
+
+/// Payload of a reply packet.
+struct Reply {
+    buffer: [u8; PACKET_SIZE],
+    len: usize,
+}
+
+impl Reply {
+    const fn new() -> Self {
+        Self {
+            buffer: [0; PACKET_SIZE],
+            len: 0,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    const fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn push(&mut self, byte: u8) {
+        if let Some(slot) = self.buffer.get_mut(self.len) {
+            *slot = byte;
+            self.len += 1;
+        }
+    }
+
+    fn push_str(&mut self, string: &[u8]) {
+        for &byte in string {
+            self.push(byte);
+        }
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        let [high, low] = hex_byte(byte);
+        self.push(high);
+        self.push(low);
+    }
+
+    /// Pushes a number in hexadecimal, most significant digit first.
+    fn push_hex(&mut self, value: u64) {
+        let digits = (value.max(1).ilog2() + 1).div_ceil(4);
+        for digit in (0..digits).rev() {
+            let nibble = ((value >> (digit * 4)) & 0xF) as u8;
+            self.push(HEX_DIGITS[usize::from(nibble)]);
+        }
+    }
+
+    /// Pushes a register value, in target (little endian) byte order.
+    ///
+    /// # Parameters
+    /// * `value` - The register value,
+    /// * `bytes` - Width of the register.
+    fn push_le(&mut self, value: u64, bytes: u32) {
+        for byte in 0..bytes {
+            #[expect(clippy::cast_possible_truncation)]
+            self.push_byte((value >> (byte * 8)) as u8);
+        }
+    }
+
+    fn ok(&mut self) {
+        self.push_str(b"OK");
+    }
+
+    fn error(&mut self) {
+        self.push_str(b"E01");
+    }
+
+    fn status(&mut self, success: bool) {
+        if success {
+            self.ok();
+        } else {
+            self.error();
+        }
+    }
+
+    fn stop(&mut self, signal: Signal) {
+        self.push(b'S');
+        self.push_byte(signal as u8);
+    }
+}
+
+/// Lowercase hexadecimal digits.
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Hexadecimal representation of a byte.
+fn hex_byte(byte: u8) -> [u8; 2] {
+    [
+        HEX_DIGITS[usize::from(byte >> 4)],
+        HEX_DIGITS[usize::from(byte & 0xF)],
+    ]
+}
+
+/// Parses a big endian hexadecimal number.
+fn parse_hex(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() || digits.len() > 16 {
+        return None;
+    }
+    digits.iter().try_fold(0, |value, &digit| {
+        let nibble = char::from(digit).to_digit(16)?;
+        Some(value << 4 | u64::from(nibble))
+    })
+}
+
+/// Parses a register value sent in target (little endian) byte order.
+fn parse_le(digits: &[u8]) -> Option<u64> {
+    if digits.len() % 2 != 0 || digits.len() > 16 {
+        return None;
+    }
+    let (bytes, _) = digits.as_chunks::<2>();
+    bytes
+        .iter()
+        .enumerate()
+        .try_fold(0, |value, (index, byte)| {
+            Some(value | parse_hex(byte)? << (index * 8))
+        })
+}
+
+/// Splits a slice on the first occurrence of a separator.
+fn split_once(bytes: &[u8], separator: u8) -> Option<(&[u8], &[u8])> {
+    let position = bytes.iter().position(|&byte| byte == separator)?;
+    Some((&bytes[..position], &bytes[position + 1..]))
+}
+
+/// Parses an `addr,length` pair.
+fn parse_range(args: &[u8]) -> Option<(u64, u64)> {
+    let (address, length) = split_once(args, b',')?;
+    Some((parse_hex(address)?, parse_hex(length)?))
+}
+
+/// Whether a byte of memory can be accessed without faulting.
+fn is_mapped(address: u64) -> bool {
+    VirtAddr::try_new(address).is_ok_and(|address| translate_addr(address).is_some())
+}
+
+/// Handles a `m addr,length` packet.
+fn read_memory(args: &[u8], reply: &mut Reply) -> bool {
+    let Some((address, length)) = parse_range(args) else {
+        return false;
+    };
+    let length = length.min(MAX_MEMORY_READ);
+    for offset in 0..length {
+        let Some(address) = address
+            .checked_add(offset)
+            .filter(|&address| is_mapped(address))
+        else {
+            return false;
+        };
+        // SAFETY:
+        // The address is mapped.
+        reply.push_byte(unsafe { ptr::read_volatile(address as *const u8) });
+    }
+    true
+}
+
+/// Writes a byte of memory, even if the page is read only.
+///
+/// # Safety
+/// The address must be mapped, and the write must not break the kernel.
+unsafe fn poke(address: u64, value: u8) {
+    // clear the write protection to patch the (read only) kernel code
+    let flags = Cr0::read();
+    Cr0::write(flags - Cr0Flags::WRITE_PROTECT);
+    ptr::write_volatile(address as *mut u8, value);
+    Cr0::write(flags);
+}
+
+/// Handles a `M addr,length:XX...` packet.
+fn write_memory(args: &[u8]) -> bool {
+    let Some((range, data)) = split_once(args, b':') else {
+        return false;
+    };
+    let Some((address, length)) = parse_range(range) else {
+        return false;
+    };
+    // the length comes from the wire: reject it rather than overflow
+    if length.checked_mul(2) != Some(data.len() as u64) {
+        return false;
+    }
+    let (bytes, _) = data.as_chunks::<2>();
+    for (target, byte) in (address..).zip(bytes) {
+        let Some(byte) = parse_hex(byte).and_then(|byte| u8::try_from(byte).ok()) else {
+            return false;
+        };
+        if !is_mapped(target) {
+            return false;
+        }
+        // SAFETY:
+        // The address is mapped, and the debugger asked for it.
+        unsafe {
+            poke(target, byte);
+        }
+    }
+    true
+}
+
+/// Handles the `Z0,addr,kind` and `z0,addr,kind` packets.
+///
+/// Only software breakpoints (type 0) are supported.
+///
+/// # Parameters
+/// * `args` - Arguments of the packet,
+/// * `insert` - Whether to insert or remove the breakpoint.
+fn set_breakpoint(args: &[u8], insert: bool) -> bool {
+    let Some(args) = args.strip_prefix(b"0,") else {
+        return false;
+    };
+    let Some((address, _kind)) = parse_range(args) else {
+        return false;
+    };
+    if !is_mapped(address) {
+        return false;
+    }
+
+    let mut breakpoints = BREAKPOINTS.lock();
+    let existing = breakpoints
+        .iter()
+        .position(|breakpoint| breakpoint.is_some_and(|(other, _)| other == address));
+    match (insert, existing) {
+        (true, Some(_)) => true,
+        (true, None) => {
+            let Some(slot) = breakpoints.iter_mut().find(|slot| slot.is_none()) else {
+                return false;
+            };
+            // SAFETY:
+            // The address is mapped.
+            let original = unsafe { ptr::read_volatile(address as *const u8) };
+            *slot = Some((address, original));
+            // SAFETY:
+            // The address is mapped, and the debugger asked for it.
+            unsafe {
+                poke(address, INT3);
+            }
+            true
+        }
+        (false, Some(index)) => {
+            if let Some((_, original)) = breakpoints[index].take() {
+                // SAFETY:
+                // Restores the original instruction.
+                unsafe {
+                    poke(address, original);
+                }
+            }
+            true
+        }
+        (false, None) => false,
+    }
+}
+
+/// Numbers are parsed and formatted the way the debugger expects.
+#[test_case]
+fn hex_encoding() {
+    assert_eq!(parse_hex(b"1f"), Some(0x1F), "wrong big endian number");
+    assert_eq!(
+        parse_le(b"3412"),
+        Some(0x1234),
+        "wrong little endian number"
+    );
+    assert_eq!(parse_hex(b"zz"), None, "invalid number parsed");
+
+    let mut reply = Reply::new();
+    reply.push_le(0x1234, 4);
+    reply.push_str(b" ");
+    reply.push_hex(0x400);
+    assert_eq!(reply.as_bytes(), b"34120000 400", "wrong encoding");
+}
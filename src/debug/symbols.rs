@@ -0,0 +1,113 @@
+// File: src/debug/symbols.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{hint::black_box, ptr, slice, str};
+
+/// Marker at the start of the symbol table section.
+const MAGIC: &[u8; 8] = b"KSYMTAB\n";
+/// Size of the symbol table, header included.
+///
+/// Must match the section size used by `scripts/embed-symbols.fish`.
+const TABLE_SIZE: usize = 1024 * 1024;
+
+/// Symbol table of the kernel.
+///
+/// The section is reserved at compile time and filled after linking by
+/// `scripts/embed-symbols.fish` (called by the cargo runner) with one
+/// `<address> <size> <name>` line per function, in hexadecimal and sorted by
+/// address. The rest of the section is zeroed.
+#[used]
+#[link_section = ".ksyms"]
+static SYMBOL_TABLE: SymbolTable = SymbolTable {
+    magic: *MAGIC,
+    entries: [0; TABLE_SIZE - MAGIC.len()],
+};
+
+/// Layout of the `.ksyms` section.
+#[repr(C)]
+struct SymbolTable {
+    magic: [u8; MAGIC.len()],
+    entries: [u8; TABLE_SIZE - MAGIC.len()],
+}
+
+/// A function of the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    /// Demangled name of the function.
+    pub name: &'static str,
+    /// Address of the first instruction of the function.
+    pub address: u64,
+    /// Size of the function, in bytes.
+    pub size: u64,
+}
+
+/// The lines of the symbol table.
+///
+/// Empty if the symbol table was not embedded in the kernel image.
+fn entries() -> impl Iterator<Item = &'static str> {
+    // The table is patched after compilation: the compiler must not assume
+    // it still holds its initial (zeroed) value.
+    let table = black_box(ptr::addr_of!(SYMBOL_TABLE.entries)).cast::<u8>();
+    // SAFETY:
+    // The table is a static, valid for its whole size.
+    let bytes = unsafe { slice::from_raw_parts(table, TABLE_SIZE - MAGIC.len()) };
+    let end = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    str::from_utf8(&bytes[..end]).unwrap_or_default().lines()
+}
+
+/// Parses a line of the symbol table.
+///
+/// # Parameters
+/// * `line` - The `<address> <size> <name>` line.
+fn parse(line: &'static str) -> Option<Symbol> {
+    let mut fields = line.splitn(3, ' ');
+    let address = u64::from_str_radix(fields.next()?, 16).ok()?;
+    let size = u64::from_str_radix(fields.next()?, 16).ok()?;
+    let name = fields.next()?;
+    Some(Symbol {
+        name,
+        address,
+        size,
+    })
+}
+
+/// Finds the function containing an address.
+///
+/// # Parameters
+/// * `address` - The address to resolve.
+#[must_use]
+pub fn resolve(address: u64) -> Option<Symbol> {
+    entries()
+        .filter_map(parse)
+        .take_while(|symbol| symbol.address <= address)
+        .filter(|symbol| address < symbol.address + symbol.size.max(1))
+        .last()
+}
@@ -26,9 +26,10 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use alloc::{boxed::Box, vec};
 use lazy_static::lazy_static;
 use x86_64::instructions::tables::load_tss;
-use x86_64::registers::segmentation::{Segment, CS};
+use x86_64::registers::segmentation::{Segment, CS, SS};
 use x86_64::structures::gdt::SegmentSelector;
 use x86_64::structures::gdt::{Descriptor, GlobalDescriptorTable};
 use x86_64::structures::tss::TaskStateSegment;
@@ -36,47 +37,131 @@ use x86_64::VirtAddr;
 
 /// Interrupt Stack Table (IST) used for Double Faults stacks.
 pub const DOUBLE_FAULT_IST_INDEX: u16 = 0;
+/// Interrupt Stack Table (IST) used for Non-Maskable Interrupts stacks.
+pub const NMI_IST_INDEX: u16 = 1;
+/// Interrupt Stack Table (IST) used for Machine Checks stacks.
+pub const MACHINE_CHECK_IST_INDEX: u16 = 2;
+
+/// Size of each of the Interrupt Stack Table stacks.
+const STACK_SIZE: usize = 4096 * 5;
+
+/// Reserves a new stack, and evaluates to its top address.
+///
+/// Those faults can arrive while the current stack is unusable
+/// (overflowed or corrupted), so each of them gets a known good one.
+/// Interrupts coming from user mode also get their own kernel stack.
+macro_rules! ist_stack {
+    () => {{
+        static mut STACK: [u8; STACK_SIZE] = [0; STACK_SIZE];
+
+        #[expect(static_mut_refs)]
+        let stack_start = VirtAddr::from_ptr(unsafe { &STACK });
+        stack_start + STACK_SIZE as u64
+    }};
+}
 
 lazy_static! {
     static ref TSS: TaskStateSegment = {
         let mut tss = TaskStateSegment::new();
-        tss.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = {
-            const STACK_SIZE: usize = 4096 * 5;
-            static mut STACK: [u8; STACK_SIZE] = [0; STACK_SIZE];
-
-            #[expect(static_mut_refs)]
-            let stack_start = VirtAddr::from_ptr(unsafe { &STACK });
-            stack_start + STACK_SIZE as u64
-        };
+        tss.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = ist_stack!();
+        tss.interrupt_stack_table[NMI_IST_INDEX as usize] = ist_stack!();
+        tss.interrupt_stack_table[MACHINE_CHECK_IST_INDEX as usize] = ist_stack!();
+        tss.privilege_stack_table[0] = ist_stack!();
         tss
     };
 }
 
 lazy_static! {
-    static ref GDT: (GlobalDescriptorTable, Selectors) = {
-        let mut gdt = GlobalDescriptorTable::new();
-        let code_selector = gdt.append(Descriptor::kernel_code_segment());
-        let tss_selector = gdt.append(Descriptor::tss_segment(&TSS));
-        (
-            gdt,
-            Selectors {
-                code_selector,
-                tss_selector,
-            },
-        )
-    };
+    static ref GDT: (GlobalDescriptorTable, Selectors) = new_gdt(&TSS);
+}
+
+/// Builds a Global Descriptor Table using the given Task State Segment.
+fn new_gdt(tss: &'static TaskStateSegment) -> (GlobalDescriptorTable, Selectors) {
+    let mut gdt = GlobalDescriptorTable::new();
+    // The order of the segments is imposed by SYSCALL and SYSRET.
+    let code = gdt.append(Descriptor::kernel_code_segment());
+    let data = gdt.append(Descriptor::kernel_data_segment());
+    let user_data = gdt.append(Descriptor::user_data_segment());
+    let user_code = gdt.append(Descriptor::user_code_segment());
+    let tss = gdt.append(Descriptor::tss_segment(tss));
+    (
+        gdt,
+        Selectors {
+            code,
+            data,
+            user_code,
+            user_data,
+            tss,
+        },
+    )
+}
+
+/// Allocates a stack on the heap, and returns its top address.
+fn heap_stack() -> VirtAddr {
+    let stack = vec![0_u8; STACK_SIZE].leak();
+    VirtAddr::from_ptr(stack.as_ptr_range().end)
 }
 
-struct Selectors {
-    code_selector: SegmentSelector,
-    tss_selector: SegmentSelector,
+/// Segment selectors of the Global Descriptor Table.
+pub struct Selectors {
+    /// Kernel code segment.
+    pub code: SegmentSelector,
+    /// Kernel data segment.
+    pub data: SegmentSelector,
+    /// User code segment.
+    pub user_code: SegmentSelector,
+    /// User data segment.
+    pub user_data: SegmentSelector,
+    /// Task State Segment.
+    pub tss: SegmentSelector,
+}
+
+/// Segment selectors of the Global Descriptor Table.
+pub fn selectors() -> &'static Selectors {
+    &GDT.1
 }
 
 /// Load the Global Descriptor Table.
 pub fn init() {
-    GDT.0.load();
+    load(&GDT);
+}
+
+/// Loads a new Global Descriptor Table and Task State Segment, with their
+/// own stacks, on an application processor.
+///
+/// They are never freed: processors are not stopped.
+pub fn init_ap() {
+    let mut tss = TaskStateSegment::new();
+    tss.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = heap_stack();
+    tss.interrupt_stack_table[NMI_IST_INDEX as usize] = heap_stack();
+    tss.interrupt_stack_table[MACHINE_CHECK_IST_INDEX as usize] = heap_stack();
+    tss.privilege_stack_table[0] = heap_stack();
+    let tss = Box::leak(Box::new(tss));
+    load(Box::leak(Box::new(new_gdt(tss))));
+}
+
+/// Loads a Global Descriptor Table, and its segments.
+fn load(gdt: &'static (GlobalDescriptorTable, Selectors)) {
+    let (table, selectors) = gdt;
+    table.load();
     unsafe {
-        CS::set_reg(GDT.1.code_selector);
-        load_tss(GDT.1.tss_selector);
+        CS::set_reg(selectors.code);
+        SS::set_reg(selectors.data);
+        load_tss(selectors.tss);
+    }
+}
+
+/// Every fault with its own stack gets a distinct one.
+#[test_case]
+fn ist_stacks_are_distinct() {
+    let stacks = [
+        TSS.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize],
+        TSS.interrupt_stack_table[NMI_IST_INDEX as usize],
+        TSS.interrupt_stack_table[MACHINE_CHECK_IST_INDEX as usize],
+        TSS.privilege_stack_table[0],
+    ];
+    for (index, stack) in stacks.iter().enumerate() {
+        assert!(!stack.is_null(), "stack {index} not set");
+        assert!(!stacks[..index].contains(stack), "stack {index} is shared");
     }
 }
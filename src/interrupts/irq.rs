@@ -0,0 +1,216 @@
+// File: src/interrupts/irq.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use x86_64::structures::idt::{HandlerFunc, InterruptDescriptorTable, InterruptStackFrame};
+
+pub use super::pic::{spurious_interrupts, SpuriousInterrupts};
+use super::{
+    pic::{self, PIC_1_OFFSET},
+    stats,
+};
+use crate::{spinlock::IrqSpinlock, thread};
+
+/// Number of IRQ lines handled by the chained PICs.
+pub const IRQ_LINES: u8 = 16;
+
+/// A function called when an IRQ fires.
+///
+/// Handlers run in interrupt context: they must not block, and must not
+/// (un)register handlers on their own line.
+pub type IrqHandler = fn();
+
+/// Errors raised by the IRQ registration API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IrqError {
+    /// The IRQ line does not exist on the chained PICs.
+    InvalidLine(u8),
+    /// The handler was already unregistered.
+    NotRegistered,
+}
+
+/// Handle to a registered IRQ handler, used to unregister it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IrqHandle {
+    /// IRQ line the handler is registered on.
+    irq: u8,
+    /// Unique identifier of the registration.
+    id: u64,
+}
+
+impl IrqHandle {
+    /// IRQ line the handler is registered on.
+    #[must_use]
+    pub const fn irq(&self) -> u8 {
+        self.irq
+    }
+}
+
+/// Handlers registered on each IRQ line, in registration order.
+static HANDLERS: [IrqSpinlock<Vec<(u64, IrqHandler)>>; IRQ_LINES as usize] =
+    [const { IrqSpinlock::new(Vec::new()) }; IRQ_LINES as usize];
+
+/// Registers a handler for the given IRQ line.
+///
+/// Several handlers may share a line, they are then called in registration
+/// order. The line is unmasked on the PIC when its first handler is
+/// registered.
+///
+/// # Parameters
+/// * `irq` - The IRQ line (0 to 15),
+/// * `handler` - The function to call when the IRQ fires.
+///
+/// # Errors
+/// If the IRQ line does not exist.
+pub fn register_irq(irq: u8, handler: IrqHandler) -> Result<IrqHandle, IrqError> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let line = HANDLERS
+        .get(usize::from(irq))
+        .ok_or(IrqError::InvalidLine(irq))?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut handlers = line.lock();
+    handlers.push((id, handler));
+    if handlers.len() == 1 {
+        pic::unmask(irq);
+    }
+    drop(handlers);
+
+    Ok(IrqHandle { irq, id })
+}
+
+/// Unregisters a handler previously added with [`register_irq`].
+///
+/// The line is masked on the PIC once its last handler is gone.
+///
+/// # Parameters
+/// * `handle` - The handle returned on registration.
+///
+/// # Errors
+/// If the handler was already unregistered.
+pub fn unregister_irq(handle: IrqHandle) -> Result<(), IrqError> {
+    let line = HANDLERS
+        .get(usize::from(handle.irq))
+        .ok_or(IrqError::InvalidLine(handle.irq))?;
+    let mut handlers = line.lock();
+    let count = handlers.len();
+    handlers.retain(|(id, _)| *id != handle.id);
+    if handlers.len() == count {
+        return Err(IrqError::NotRegistered);
+    }
+    if handlers.is_empty() {
+        pic::mask(handle.irq);
+    }
+    Ok(())
+}
+
+/// Calls the handlers registered on an IRQ line, then acknowledges it.
+///
+/// Spurious interrupts are counted and dropped without calling any handler.
+///
+/// # Parameters
+/// * `irq` - The IRQ line that fired.
+fn dispatch(irq: u8) {
+    stats::record(PIC_1_OFFSET + irq);
+    if pic::is_spurious(irq) {
+        return;
+    }
+    if let Some(line) = HANDLERS.get(usize::from(irq)) {
+        for (_, handler) in line.lock().iter() {
+            handler();
+        }
+    }
+    pic::end_of_interrupt(irq);
+    // the current thread may be switched out until its next time slice
+    thread::preempt();
+}
+
+/// Generates one interrupt entry point per IRQ line, all forwarding to
+/// [`dispatch`].
+macro_rules! irq_stubs {
+    ($($irq:literal => $stub:ident),* $(,)?) => {
+        $(
+            extern "x86-interrupt" fn $stub(_stack_frame: InterruptStackFrame) {
+                dispatch($irq);
+            }
+        )*
+
+        /// Entry points of the IRQ lines, indexed by line.
+        const STUBS: [HandlerFunc; IRQ_LINES as usize] = [$($stub),*];
+    };
+}
+
+irq_stubs! {
+    0 => irq0, 1 => irq1, 2 => irq2, 3 => irq3,
+    4 => irq4, 5 => irq5, 6 => irq6, 7 => irq7,
+    8 => irq8, 9 => irq9, 10 => irq10, 11 => irq11,
+    12 => irq12, 13 => irq13, 14 => irq14, 15 => irq15,
+}
+
+/// Installs the IRQ entry points in the IDT.
+///
+/// # Parameters
+/// * `idt` - The IDT being built.
+pub(super) fn install(idt: &mut InterruptDescriptorTable) {
+    for (vector, stub) in (PIC_1_OFFSET..).zip(STUBS) {
+        idt[vector].set_handler_fn(stub);
+    }
+}
+
+/// Handlers can be added to and removed from a line, shared or not.
+#[test_case]
+fn register_and_unregister() {
+    // IRQ5 is usually free (second parallel port / sound card)
+    const LINE: u8 = 5;
+    const fn handler() {}
+
+    let first = register_irq(LINE, handler).expect("registration failed");
+    let second = register_irq(LINE, handler).expect("registration failed");
+    assert_eq!(
+        unregister_irq(first),
+        Ok(()),
+        "could not unregister handler"
+    );
+    assert_eq!(
+        unregister_irq(first),
+        Err(IrqError::NotRegistered),
+        "handler unregistered twice"
+    );
+    assert_eq!(
+        unregister_irq(second),
+        Ok(()),
+        "could not unregister handler"
+    );
+    assert_eq!(
+        register_irq(IRQ_LINES, handler),
+        Err(IrqError::InvalidLine(IRQ_LINES)),
+        "registered a handler on a missing line"
+    );
+}
@@ -0,0 +1,150 @@
+// File: src/interrupts/lapic.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{hint, ptr};
+
+use conquer_once::spin::OnceCell;
+use x86_64::{
+    structures::{
+        idt::InterruptStackFrame,
+        paging::{mapper::MapToError, Size4KiB},
+    },
+    PhysAddr, VirtAddr,
+};
+
+use super::stats;
+use crate::paging::map_mmio;
+
+/// Vector of the spurious interrupts of the local APIC.
+pub const SPURIOUS_VECTOR: u8 = 0xFF;
+
+/// Local APIC identifier register.
+const ID: usize = 0x20;
+/// Spurious interrupt vector register.
+const SPURIOUS_INTERRUPT: usize = 0xF0;
+/// Error status register.
+const ERROR_STATUS: usize = 0x280;
+/// Interrupt command register, low half.
+const INTERRUPT_COMMAND_LOW: usize = 0x300;
+/// Interrupt command register, high half (destination).
+const INTERRUPT_COMMAND_HIGH: usize = 0x310;
+/// Size of the registers.
+const REGISTERS_SIZE: u64 = 0x400;
+
+/// Software enable bit of the spurious interrupt vector register.
+const APIC_ENABLE: u32 = 1 << 8;
+/// Delivery mode of an INIT inter-processor interrupt.
+const DELIVERY_INIT: u32 = 0b101 << 8;
+/// Delivery mode of a STARTUP inter-processor interrupt.
+const DELIVERY_STARTUP: u32 = 0b110 << 8;
+/// Asserted level.
+const LEVEL_ASSERT: u32 = 1 << 14;
+/// The interrupt command is still being sent.
+const DELIVERY_PENDING: u32 = 1 << 12;
+
+/// Virtual address of the local APIC registers.
+static BASE: OnceCell<VirtAddr> = OnceCell::uninit();
+
+/// Maps the local APIC registers, and enables the local APIC of the
+/// current processor.
+///
+/// # Parameters
+/// * `addr` - Physical address of the local APIC registers (from the MADT).
+///
+/// # Errors
+/// If the registers could not be mapped.
+pub fn init(addr: PhysAddr) -> Result<(), MapToError<Size4KiB>> {
+    let base = map_mmio(addr, REGISTERS_SIZE)?;
+    BASE.init_once(|| base);
+    enable();
+    Ok(())
+}
+
+/// Enables the local APIC of the current processor.
+pub fn enable() {
+    write(SPURIOUS_INTERRUPT, APIC_ENABLE | u32::from(SPURIOUS_VECTOR));
+}
+
+/// Identifier of the local APIC of the current processor.
+///
+/// Returns `None` if the local APIC is not initialized.
+#[must_use]
+pub fn id() -> Option<u8> {
+    BASE.get().map(|_| (read(ID) >> 24) as u8)
+}
+
+/// Sends an INIT inter-processor interrupt, resetting the processor.
+///
+/// # Parameters
+/// * `apic_id` - The destination local APIC.
+pub fn send_init(apic_id: u8) {
+    send(apic_id, DELIVERY_INIT | LEVEL_ASSERT);
+}
+
+/// Sends a STARTUP inter-processor interrupt, starting the processor in real
+/// mode at `vector * 0x1000`.
+///
+/// # Parameters
+/// * `apic_id` - The destination local APIC,
+/// * `vector` - The page of the startup code.
+pub fn send_startup(apic_id: u8, vector: u8) {
+    send(apic_id, DELIVERY_STARTUP | LEVEL_ASSERT | u32::from(vector));
+}
+
+/// Sends an inter-processor interrupt, and waits for its delivery.
+fn send(apic_id: u8, command: u32) {
+    write(ERROR_STATUS, 0);
+    write(INTERRUPT_COMMAND_HIGH, u32::from(apic_id) << 24);
+    write(INTERRUPT_COMMAND_LOW, command);
+    while read(INTERRUPT_COMMAND_LOW) & DELIVERY_PENDING != 0 {
+        hint::spin_loop();
+    }
+}
+
+/// Reads a register.
+fn read(register: usize) -> u32 {
+    BASE.get().map_or(0, |base| {
+        // SAFETY:
+        // The registers are mapped at the base.
+        unsafe { ptr::read_volatile((*base + register as u64).as_ptr()) }
+    })
+}
+
+/// Writes a register.
+fn write(register: usize, value: u32) {
+    if let Some(base) = BASE.get() {
+        // SAFETY:
+        // The registers are mapped at the base.
+        unsafe { ptr::write_volatile((*base + register as u64).as_mut_ptr(), value) }
+    }
+}
+
+/// Spurious interrupts need no end of interrupt.
+pub(super) extern "x86-interrupt" fn spurious_handler(_stack_frame: InterruptStackFrame) {
+    stats::record(SPURIOUS_VECTOR);
+}
@@ -0,0 +1,241 @@
+// File: src/interrupts/mce.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{arch::x86_64::__cpuid, fmt};
+
+use x86_64::registers::{
+    control::{Cr4, Cr4Flags},
+    model_specific::Msr,
+};
+
+/// Machine Check Global Capability register.
+const IA32_MCG_CAP: u32 = 0x179;
+/// Machine Check Global Status register.
+const IA32_MCG_STATUS: u32 = 0x17A;
+/// Status register of the first error reporting bank.
+const IA32_MC0_STATUS: u32 = 0x401;
+/// Number of MSRs of each bank (control, status, address, misc).
+const BANK_REGISTERS: u32 = 4;
+
+/// `CPUID.01H:EDX` bit advertising the Machine Check Exception.
+const CPUID_MCE: u32 = 1 << 7;
+/// `CPUID.01H:EDX` bit advertising the Machine Check Architecture.
+const CPUID_MCA: u32 = 1 << 14;
+
+/// The bank holds a valid error.
+const STATUS_VALID: u64 = 1 << 63;
+/// An error was lost because the bank already held one.
+const STATUS_OVERFLOW: u64 = 1 << 62;
+/// The error was not corrected by the hardware.
+const STATUS_UNCORRECTED: u64 = 1 << 61;
+/// The error was enabled in the bank control register.
+const STATUS_ENABLED: u64 = 1 << 60;
+/// The misc register holds additional information.
+const STATUS_MISC_VALID: u64 = 1 << 59;
+/// The address register holds the address of the error.
+const STATUS_ADDR_VALID: u64 = 1 << 58;
+/// The processor context is corrupted.
+const STATUS_CONTEXT_CORRUPT: u64 = 1 << 57;
+
+/// Whether the CPU supports machine checks and their architecture.
+#[must_use]
+pub fn supported() -> bool {
+    let features = __cpuid(1).edx;
+    features & CPUID_MCE != 0 && features & CPUID_MCA != 0
+}
+
+/// Enables the Machine Check Exception, if the CPU supports it.
+pub fn init() {
+    if supported() {
+        // SAFETY:
+        // Only adds the MCE flag, a handler is installed in the IDT.
+        unsafe {
+            Cr4::update(|flags| flags.insert(Cr4Flags::MACHINE_CHECK_EXCEPTION));
+        }
+    }
+}
+
+/// An error reported by one of the machine check banks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankError {
+    /// Index of the reporting bank.
+    pub bank: u32,
+    /// Raw content of the status register.
+    pub status: u64,
+    /// Address of the error, if reported.
+    pub address: Option<u64>,
+    /// Model specific information, if reported.
+    pub misc: Option<u64>,
+}
+
+impl BankError {
+    /// Reads the error held by a bank, if any.
+    ///
+    /// # Parameters
+    /// * `bank` - Index of the bank.
+    ///
+    /// # Safety
+    /// The bank must exist, as reported by `IA32_MCG_CAP`.
+    unsafe fn read(bank: u32) -> Option<Self> {
+        let base = IA32_MC0_STATUS + bank * BANK_REGISTERS;
+        let status = Msr::new(base).read();
+        if status & STATUS_VALID == 0 {
+            return None;
+        }
+        let address = (status & STATUS_ADDR_VALID != 0).then(|| Msr::new(base + 1).read());
+        let misc = (status & STATUS_MISC_VALID != 0).then(|| Msr::new(base + 2).read());
+        Some(Self {
+            bank,
+            status,
+            address,
+            misc,
+        })
+    }
+
+    /// Architectural error code (bits 0 to 15 of the status).
+    #[must_use]
+    pub const fn error_code(&self) -> u16 {
+        (self.status & 0xFFFF) as u16
+    }
+
+    /// Whether the hardware failed to correct the error.
+    #[must_use]
+    pub const fn is_uncorrected(&self) -> bool {
+        self.status & STATUS_UNCORRECTED != 0
+    }
+
+    /// Whether the processor context is corrupted, and execution cannot resume.
+    #[must_use]
+    pub const fn is_context_corrupt(&self) -> bool {
+        self.status & STATUS_CONTEXT_CORRUPT != 0
+    }
+
+    /// Coarse classification of the architectural error code.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        let code = self.error_code();
+        match code {
+            0x0000 => "no error",
+            0x0001 => "unclassified",
+            0x0002 => "microcode ROM parity",
+            0x0003 => "external",
+            0x0004 => "FRC",
+            0x0005 => "internal parity",
+            0x0400 => "internal timer",
+            _ if code & 0xFC00 == 0x0400 => "internal unclassified",
+            _ if code & 0xEFFC == 0x000C => "generic cache hierarchy",
+            _ if code & 0xEFF0 == 0x0010 => "TLB",
+            _ if code & 0xEF80 == 0x0080 => "memory controller",
+            _ if code & 0xEF00 == 0x0100 => "cache hierarchy",
+            _ if code & 0xE800 == 0x0800 => "bus and interconnect",
+            _ => "model specific",
+        }
+    }
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bank {}: {} error (code {:#06x}, status {:#018x})",
+            self.bank,
+            self.kind(),
+            self.error_code(),
+            self.status
+        )?;
+        if self.is_uncorrected() {
+            write!(f, " uncorrected")?;
+        }
+        if self.is_context_corrupt() {
+            write!(f, " context-corrupt")?;
+        }
+        if self.status & STATUS_OVERFLOW != 0 {
+            write!(f, " overflow")?;
+        }
+        if self.status & STATUS_ENABLED == 0 {
+            write!(f, " (disabled)")?;
+        }
+        if let Some(address) = self.address {
+            write!(f, " at {address:#x}")?;
+        }
+        if let Some(misc) = self.misc {
+            write!(f, " misc {misc:#x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors currently reported by the machine check banks.
+///
+/// Empty if the CPU does not support the machine check architecture.
+pub fn bank_errors() -> impl Iterator<Item = BankError> {
+    let banks = if supported() {
+        // SAFETY:
+        // The MSR exists when MCA is supported.
+        let capabilities = unsafe { Msr::new(IA32_MCG_CAP).read() };
+        (capabilities & 0xFF) as u32
+    } else {
+        0
+    };
+    // SAFETY:
+    // There are `banks` banks, as reported by `IA32_MCG_CAP`.
+    (0..banks).filter_map(|bank| unsafe { BankError::read(bank) })
+}
+
+/// Raw content of the global machine check status register.
+#[must_use]
+pub fn global_status() -> Option<u64> {
+    // SAFETY:
+    // The MSR exists when MCA is supported.
+    supported().then(|| unsafe { Msr::new(IA32_MCG_STATUS).read() })
+}
+
+/// Status registers are decoded into the error they report.
+#[test_case]
+fn bank_status_is_decoded() {
+    let error = BankError {
+        bank: 3,
+        status: STATUS_VALID | STATUS_UNCORRECTED | STATUS_ENABLED | STATUS_ADDR_VALID | 0x0134,
+        address: Some(0x1000),
+        misc: None,
+    };
+    assert_eq!(error.error_code(), 0x0134);
+    assert_eq!(error.kind(), "cache hierarchy");
+    assert!(error.is_uncorrected(), "uncorrected flag lost");
+    assert!(!error.is_context_corrupt(), "context reported corrupt");
+    let corrected = BankError {
+        status: STATUS_VALID | STATUS_CONTEXT_CORRUPT | 0x0011,
+        ..error
+    };
+    assert_eq!(corrected.kind(), "TLB");
+    assert!(
+        !corrected.is_uncorrected(),
+        "corrected error reported uncorrected"
+    );
+    assert!(corrected.is_context_corrupt(), "context corruption lost");
+}
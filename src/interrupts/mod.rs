@@ -1,36 +1,69 @@
 use lazy_static::lazy_static;
 use pic::{keyboard_interrupt_handler, timer_interrupt_handler, InterruptIndex};
+use trap::TrapFrame;
 use x86_64::{
     instructions::interrupts as x86_64_interrupts,
     registers::control::Cr2,
-    structures::idt::{InterruptDescriptorTable, InterruptStackFrame, PageFaultErrorCode},
+    structures::idt::{
+        ExceptionVector, InterruptDescriptorTable, InterruptStackFrame, PageFaultErrorCode,
+    },
+    PrivilegeLevel, VirtAddr,
 };
 
-use crate::{hlt_loop, println};
+use crate::{
+    debug::{backtrace::Backtrace, gdb},
+    emergency_println, hlt_loop, println, user,
+};
 
 /// The Interrupt Stack Tables & Task State Segments definitions
 /// for the Global Descriptor Table.
 pub mod gdt;
+/// Dynamic registration of IRQ handlers.
+pub mod irq;
+/// Local Advanced Programmable Interrupt Controller.
+pub mod lapic;
+/// Machine Check Architecture.
+pub mod mce;
 /// Hardware interrupts
 pub mod pic;
+/// Programmable Interval Timer.
+pub mod pit;
+/// Per-vector interrupt statistics.
+pub mod stats;
+/// Full register state of trapping code.
+pub mod trap;
 
 lazy_static! {
     static ref IDT: InterruptDescriptorTable = {
         let mut idt = InterruptDescriptorTable::new();
         // Fault interrupts
-        idt.breakpoint.set_handler_fn(breakpoint_handler);
         unsafe {
+            idt.breakpoint
+                .set_handler_addr(VirtAddr::from_ptr(entries::breakpoint_entry as *const ()));
+            idt.debug
+                .set_handler_addr(VirtAddr::from_ptr(entries::debug_entry as *const ()));
             idt.double_fault
                 .set_handler_fn(double_fault_handler)
                 .set_stack_index(gdt::DOUBLE_FAULT_IST_INDEX);
+            idt.non_maskable_interrupt
+                .set_handler_fn(nmi_handler)
+                .set_stack_index(gdt::NMI_IST_INDEX);
+            idt.machine_check
+                .set_handler_fn(machine_check_handler)
+                .set_stack_index(gdt::MACHINE_CHECK_IST_INDEX);
         }
+        // Page faults stay on the current stack: a fault in their handler
+        // would reset an IST stack and overwrite the outer frame. Kernel stack
+        // overflows end up in the double fault handler instead.
         idt.page_fault.set_handler_fn(page_fault_handler);
+        idt.divide_error.set_handler_fn(divide_error_handler);
+        idt.invalid_opcode.set_handler_fn(invalid_opcode_handler);
+        idt.general_protection_fault
+            .set_handler_fn(general_protection_fault_handler);
 
         // Hardware interrupts
-        idt[InterruptIndex::Timer.as_u8()]
-            .set_handler_fn(timer_interrupt_handler);
-        idt[InterruptIndex::Keyboard.as_u8()]
-            .set_handler_fn(keyboard_interrupt_handler);
+        irq::install(&mut idt);
+        idt[lapic::SPURIOUS_VECTOR].set_handler_fn(lapic::spurious_handler);
 
         idt
     };
@@ -40,26 +73,135 @@ lazy_static! {
 pub fn init() {
     gdt::init();
     IDT.load();
+    mce::init();
     pic::init();
+    pit::init();
+}
+
+/// Initializes the interrupts of an application processor.
+///
+/// The hardware interrupts stay routed to the bootstrap processor.
+pub fn init_ap() {
+    gdt::init_ap();
+    IDT.load();
+    mce::init();
+    lapic::enable();
+}
+
+/// Registers the hardware interrupt handlers, then enables interrupts.
+///
+/// The handlers are stored on the heap, which must be initialized first.
+pub fn init_hardware() {
+    irq::register_irq(InterruptIndex::Timer.as_irq(), timer_interrupt_handler)
+        .expect("timer IRQ registration failed");
+    irq::register_irq(
+        InterruptIndex::Keyboard.as_irq(),
+        keyboard_interrupt_handler,
+    )
+    .expect("keyboard IRQ registration failed");
     x86_64_interrupts::enable();
 }
 
-extern "x86-interrupt" fn breakpoint_handler(stack_frame: InterruptStackFrame) {
-    println!("EXCEPTION: BREAKPOINT\n{:#?}", stack_frame);
+/// Entry points of the exceptions handled with the full register state.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+mod entries {
+    use super::{breakpoint_handler, debug_handler, trap::trap_entry};
+
+    trap_entry!(breakpoint_entry => breakpoint_handler);
+    trap_entry!(debug_entry => debug_handler);
+}
+
+extern "C" fn breakpoint_handler(frame: &mut TrapFrame) {
+    stats::record(ExceptionVector::Breakpoint as u8);
+    if gdb::is_enabled() {
+        gdb::handle_trap(frame, gdb::Signal::Trap);
+    } else {
+        println!("EXCEPTION: BREAKPOINT\n{frame}");
+    }
+}
+
+extern "C" fn debug_handler(frame: &mut TrapFrame) {
+    stats::record(ExceptionVector::Debug as u8);
+    if gdb::is_enabled() {
+        gdb::handle_trap(frame, gdb::Signal::Trap);
+    } else {
+        println!("EXCEPTION: DEBUG\n{frame}");
+        frame.set_single_step(false);
+    }
+}
+
+/// Kills the user program that caused an exception, if the exception comes
+/// from user mode.
+///
+/// # Parameters
+/// * `vector` - The exception,
+/// * `stack_frame` - The interrupted context.
+fn kill_user_program(vector: ExceptionVector, stack_frame: &InterruptStackFrame) {
+    if stack_frame.code_segment.rpl() == PrivilegeLevel::Ring3 {
+        println!(
+            "USER FAULT: {vector:?} at {:?}, killing the program",
+            stack_frame.instruction_pointer
+        );
+        user::kill(vector as u8);
+    }
+}
+
+extern "x86-interrupt" fn divide_error_handler(stack_frame: InterruptStackFrame) {
+    stats::record(ExceptionVector::Division as u8);
+    kill_user_program(ExceptionVector::Division, &stack_frame);
+    panic!("EXCEPTION: DIVIDE ERROR\n{stack_frame:#?}");
+}
+
+extern "x86-interrupt" fn invalid_opcode_handler(stack_frame: InterruptStackFrame) {
+    stats::record(ExceptionVector::InvalidOpcode as u8);
+    kill_user_program(ExceptionVector::InvalidOpcode, &stack_frame);
+    panic!("EXCEPTION: INVALID OPCODE\n{stack_frame:#?}");
+}
+
+extern "x86-interrupt" fn general_protection_fault_handler(
+    stack_frame: InterruptStackFrame,
+    error_code: u64,
+) {
+    stats::record(ExceptionVector::GeneralProtection as u8);
+    kill_user_program(ExceptionVector::GeneralProtection, &stack_frame);
+    panic!("EXCEPTION: GENERAL PROTECTION FAULT ({error_code:#x})\n{stack_frame:#?}");
 }
 
 extern "x86-interrupt" fn double_fault_handler(stack_frame: InterruptStackFrame, _code: u64) -> ! {
+    stats::record(ExceptionVector::Double as u8);
+    // the fault may have happened with an output locked: the panic handler
+    // uses the emergency output
     panic!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
 }
 
+extern "x86-interrupt" fn nmi_handler(stack_frame: InterruptStackFrame) {
+    stats::record(ExceptionVector::NonMaskableInterrupt as u8);
+    emergency_println!("EXCEPTION: NON-MASKABLE INTERRUPT\n{stack_frame:#?}");
+}
+
+extern "x86-interrupt" fn machine_check_handler(stack_frame: InterruptStackFrame) -> ! {
+    stats::record(ExceptionVector::MachineCheck as u8);
+    emergency_println!("EXCEPTION: MACHINE CHECK");
+    if let Some(status) = mce::global_status() {
+        emergency_println!("Global status: {status:#x}");
+    }
+    for error in mce::bank_errors() {
+        emergency_println!("{error}");
+    }
+    panic!("{stack_frame:#?}");
+}
+
 extern "x86-interrupt" fn page_fault_handler(
     stack_frame: InterruptStackFrame,
     error_code: PageFaultErrorCode,
 ) {
-    println!("EXCEPTION: PAGE FAULT");
-    println!("Accessed Address: {:?}", Cr2::read());
-    println!("Error Code: {error_code:?}");
-    println!("{stack_frame:#?}");
+    stats::record(ExceptionVector::Page as u8);
+    emergency_println!("EXCEPTION: PAGE FAULT");
+    emergency_println!("Accessed Address: {:?}", Cr2::read());
+    emergency_println!("Error Code: {error_code:?}");
+    kill_user_program(ExceptionVector::Page, &stack_frame);
+    emergency_println!("{stack_frame:#?}");
+    Backtrace::capture_exception(stack_frame.instruction_pointer).print();
     hlt_loop();
 }
 
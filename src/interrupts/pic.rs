@@ -26,22 +26,50 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use core::sync::atomic::{AtomicU64, Ordering};
+
 use lazy_static::lazy_static;
 use pc_keyboard::{layouts, DecodedKey, HandleControl, Keyboard, ScancodeSet1};
 use pic8259::ChainedPics;
-use spin::{self, Mutex};
-use x86_64::{instructions::port::Port, structures::idt::InterruptStackFrame};
+use x86_64::instructions::port::Port;
 
-use crate::{print, tasks::keyboard};
+use super::pit;
+use crate::{
+    print,
+    spinlock::IrqSpinlock,
+    tasks::{keyboard, timer},
+    thread,
+};
 
 /// Offset of the Primary Programmable Interrupt Controller.
 pub const PIC_1_OFFSET: u8 = 32;
 /// Offset of the Secondary Programmable Interrupt Controller.
 pub const PIC_2_OFFSET: u8 = PIC_1_OFFSET + 8;
 
+/// Line of the primary PIC the secondary one is chained on.
+const CASCADE_IRQ: u8 = 2;
+/// Lowest priority line of the primary PIC, raised on spurious interrupts.
+const PRIMARY_SPURIOUS_IRQ: u8 = 7;
+/// Lowest priority line of the secondary PIC, raised on spurious interrupts.
+const SECONDARY_SPURIOUS_IRQ: u8 = 15;
+
+/// Command port of the primary PIC.
+const PIC_1_COMMAND: u16 = 0x20;
+/// Command port of the secondary PIC.
+const PIC_2_COMMAND: u16 = 0xA0;
+/// OCW3 command selecting the In-Service Register for the next read.
+const READ_ISR: u8 = 0x0B;
+/// Non specific End Of Interrupt command.
+const END_OF_INTERRUPT: u8 = 0x20;
+
+/// Number of spurious IRQ7 received.
+static SPURIOUS_PRIMARY: AtomicU64 = AtomicU64::new(0);
+/// Number of spurious IRQ15 received.
+static SPURIOUS_SECONDARY: AtomicU64 = AtomicU64::new(0);
+
 /// Definition of the Programmable Interrupt Controllers.
-pub static PICS: spin::Mutex<ChainedPics> =
-    spin::Mutex::new(unsafe { ChainedPics::new(PIC_1_OFFSET, PIC_2_OFFSET) });
+pub static PICS: IrqSpinlock<ChainedPics> =
+    IrqSpinlock::new(unsafe { ChainedPics::new(PIC_1_OFFSET, PIC_2_OFFSET) });
 
 /// Inits the PICS
 pub fn init() {
@@ -50,6 +78,117 @@ pub fn init() {
     }
 }
 
+/// Unmasks an IRQ line (and the cascade line if needed).
+///
+/// # Parameters
+/// * `irq` - The IRQ line (0 to 15).
+pub fn unmask(irq: u8) {
+    let mut pics = PICS.lock();
+    unsafe {
+        let [mut primary, mut secondary] = pics.read_masks();
+        if irq < 8 {
+            primary &= !(1 << irq);
+        } else {
+            secondary &= !(1 << (irq - 8));
+            primary &= !(1 << CASCADE_IRQ);
+        }
+        pics.write_masks(primary, secondary);
+    }
+}
+
+/// Masks an IRQ line.
+///
+/// # Parameters
+/// * `irq` - The IRQ line (0 to 15).
+pub fn mask(irq: u8) {
+    let mut pics = PICS.lock();
+    unsafe {
+        let [mut primary, mut secondary] = pics.read_masks();
+        if irq < 8 {
+            primary |= 1 << irq;
+        } else {
+            secondary |= 1 << (irq - 8);
+        }
+        pics.write_masks(primary, secondary);
+    }
+}
+
+/// Notifies the PICs that an IRQ has been handled.
+///
+/// # Parameters
+/// * `irq` - The IRQ line (0 to 15).
+pub fn end_of_interrupt(irq: u8) {
+    unsafe {
+        PICS.lock().notify_end_of_interrupt(PIC_1_OFFSET + irq);
+    }
+}
+
+/// Reads the In-Service Register of a PIC.
+///
+/// # Parameters
+/// * `command_port` - Command port of the PIC.
+fn in_service_register(command_port: u16) -> u8 {
+    let mut port = Port::<u8>::new(command_port);
+    // SAFETY:
+    // Port-mapped IO, the register is read back from the command port.
+    unsafe {
+        port.write(READ_ISR);
+        port.read()
+    }
+}
+
+/// Checks whether an IRQ is a spurious one.
+///
+/// A PIC raises its lowest priority line when an interrupt goes away before
+/// being acknowledged, in which case the line is not marked in service.
+/// Spurious interrupts must not get an EOI from their own PIC, but a spurious
+/// IRQ15 still went through the primary PIC which expects one.
+///
+/// # Parameters
+/// * `irq` - The IRQ line that fired.
+pub fn is_spurious(irq: u8) -> bool {
+    let _pics = PICS.lock();
+    match irq {
+        PRIMARY_SPURIOUS_IRQ
+            if in_service_register(PIC_1_COMMAND) & (1 << PRIMARY_SPURIOUS_IRQ) == 0 =>
+        {
+            SPURIOUS_PRIMARY.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+        SECONDARY_SPURIOUS_IRQ
+            if in_service_register(PIC_2_COMMAND) & (1 << (SECONDARY_SPURIOUS_IRQ - 8)) == 0 =>
+        {
+            SPURIOUS_SECONDARY.fetch_add(1, Ordering::Relaxed);
+            let mut primary = Port::<u8>::new(PIC_1_COMMAND);
+            // SAFETY:
+            // Port-mapped IO
+            unsafe {
+                primary.write(END_OF_INTERRUPT);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Number of spurious interrupts received on each PIC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpuriousInterrupts {
+    /// Spurious IRQ7 (primary PIC).
+    pub primary: u64,
+    /// Spurious IRQ15 (secondary PIC).
+    pub secondary: u64,
+}
+
+/// Number of spurious interrupts received since boot.
+#[must_use]
+pub fn spurious_interrupts() -> SpuriousInterrupts {
+    SpuriousInterrupts {
+        primary: SPURIOUS_PRIMARY.load(Ordering::Relaxed),
+        secondary: SPURIOUS_SECONDARY.load(Ordering::Relaxed),
+    }
+}
+
 /// Index of the various interrupts in the PIC.
 #[derive(Debug, Clone, Copy)]
 #[repr(u8)]
@@ -66,30 +205,33 @@ impl InterruptIndex {
     pub const fn as_u8(self) -> u8 {
         self as u8
     }
+
+    /// IRQ line of the interrupt.
+    #[must_use]
+    pub const fn as_irq(self) -> u8 {
+        self.as_u8() - PIC_1_OFFSET
+    }
 }
 
 /// Hardware timer iterrupt handler
-pub extern "x86-interrupt" fn timer_interrupt_handler(_stack_frame: InterruptStackFrame) {
-    unsafe {
-        PICS.lock()
-            .notify_end_of_interrupt(InterruptIndex::Timer.as_u8());
-    }
+pub fn timer_interrupt_handler() {
+    let now = pit::tick();
+    timer::wake_expired(now);
+    thread::tick();
 }
 
 /// Keyboard event interrupt
-pub extern "x86-interrupt" fn keyboard_interrupt_handler(_stack_frame: InterruptStackFrame) {
+pub fn keyboard_interrupt_handler() {
     lazy_static! {
-        static ref KEYBOARD: Mutex<Keyboard<layouts::Azerty, ScancodeSet1>> = Mutex::new(
-            Keyboard::new(ScancodeSet1::new(), layouts::Azerty, HandleControl::Ignore)
-        );
+        static ref KEYBOARD: IrqSpinlock<Keyboard<layouts::Azerty, ScancodeSet1>> =
+            IrqSpinlock::new(Keyboard::new(
+                ScancodeSet1::new(),
+                layouts::Azerty,
+                HandleControl::Ignore
+            ));
     }
     let mut port = Port::new(0x60);
 
     let scancode: u8 = unsafe { port.read() };
     keyboard::add_scancode(scancode);
-
-    unsafe {
-        PICS.lock()
-            .notify_end_of_interrupt(InterruptIndex::Keyboard.as_u8());
-    }
 }
@@ -0,0 +1,76 @@
+// File: src/interrupts/pit.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use x86_64::instructions::port::Port;
+
+/// Frequency of the oscillator driving the Programmable Interval Timer.
+const PIT_BASE_FREQUENCY: u32 = 1_193_182;
+/// Frequency (in Hz) at which the timer interrupt fires.
+pub const TIMER_FREQUENCY: u32 = 100;
+
+/// Mode/Command register of the PIT.
+const COMMAND_PORT: u16 = 0x43;
+/// Data port of the first channel (the one wired to IRQ0).
+const CHANNEL_0_PORT: u16 = 0x40;
+/// Channel 0, lobyte/hibyte access, mode 3 (square wave generator).
+const CHANNEL_0_SQUARE_WAVE: u8 = 0b0011_0110;
+
+/// Reload value of the first channel to obtain [`TIMER_FREQUENCY`].
+#[expect(clippy::cast_possible_truncation, clippy::integer_division)]
+const DIVISOR: u16 = (PIT_BASE_FREQUENCY / TIMER_FREQUENCY) as u16;
+
+/// Number of timer interrupts received since boot.
+static TICKS: AtomicU64 = AtomicU64::new(0);
+
+/// Programs the first channel of the PIT to fire at [`TIMER_FREQUENCY`].
+pub fn init() {
+    let mut command = Port::<u8>::new(COMMAND_PORT);
+    let mut data = Port::<u8>::new(CHANNEL_0_PORT);
+    // SAFETY:
+    // Port-mapped IO
+    unsafe {
+        command.write(CHANNEL_0_SQUARE_WAVE);
+        data.write((DIVISOR & 0xFF) as u8);
+        data.write((DIVISOR >> 8) as u8);
+    }
+}
+
+/// Called by the timer interrupt handler.
+///
+/// Returns the new tick count.
+pub fn tick() -> u64 {
+    TICKS.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Number of timer ticks elapsed since boot.
+#[must_use]
+pub fn ticks() -> u64 {
+    TICKS.load(Ordering::Relaxed)
+}
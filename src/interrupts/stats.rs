@@ -0,0 +1,187 @@
+// File: src/interrupts/stats.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec::Vec;
+use core::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use super::{pic::PIC_1_OFFSET, pit};
+
+/// Number of vectors in the IDT.
+const VECTORS: usize = 256;
+
+/// Number of times each vector fired.
+static COUNTS: [AtomicU64; VECTORS] = [const { AtomicU64::new(0) }; VECTORS];
+/// Tick at which each vector last fired.
+static LAST_SEEN: [AtomicU64; VECTORS] = [const { AtomicU64::new(0) }; VECTORS];
+
+/// Names of the CPU exceptions, indexed by vector.
+const EXCEPTION_NAMES: [&str; 32] = [
+    "divide error",
+    "debug",
+    "non-maskable interrupt",
+    "breakpoint",
+    "overflow",
+    "bound range exceeded",
+    "invalid opcode",
+    "device not available",
+    "double fault",
+    "coprocessor segment overrun",
+    "invalid TSS",
+    "segment not present",
+    "stack-segment fault",
+    "general protection fault",
+    "page fault",
+    "reserved",
+    "x87 floating-point",
+    "alignment check",
+    "machine check",
+    "SIMD floating-point",
+    "virtualization",
+    "control protection",
+    "reserved",
+    "reserved",
+    "reserved",
+    "reserved",
+    "reserved",
+    "reserved",
+    "hypervisor injection",
+    "VMM communication",
+    "security",
+    "reserved",
+];
+
+/// Names of the legacy IRQ lines, indexed by line.
+const IRQ_NAMES: [&str; 16] = [
+    "timer",
+    "keyboard",
+    "cascade",
+    "COM2",
+    "COM1",
+    "LPT2",
+    "floppy",
+    "LPT1",
+    "RTC",
+    "IRQ9",
+    "IRQ10",
+    "IRQ11",
+    "PS/2 mouse",
+    "FPU",
+    "primary ATA",
+    "secondary ATA",
+];
+
+/// Records that an interrupt vector fired.
+///
+/// Called by the interrupt handlers, must not block or allocate.
+///
+/// # Parameters
+/// * `vector` - The vector that fired.
+pub fn record(vector: u8) {
+    let index = usize::from(vector);
+    COUNTS[index].fetch_add(1, Ordering::Relaxed);
+    LAST_SEEN[index].store(pit::ticks(), Ordering::Relaxed);
+}
+
+/// Human readable name of an interrupt vector.
+///
+/// # Parameters
+/// * `vector` - The interrupt vector.
+#[must_use]
+pub fn vector_name(vector: u8) -> &'static str {
+    let index = usize::from(vector);
+    let irq = usize::from(vector.wrapping_sub(PIC_1_OFFSET));
+    EXCEPTION_NAMES
+        .get(index)
+        .or_else(|| IRQ_NAMES.get(irq))
+        .copied()
+        .unwrap_or("unknown")
+}
+
+/// Statistics of a single interrupt vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorStats {
+    /// The interrupt vector.
+    pub vector: u8,
+    /// Name of the vector.
+    pub name: &'static str,
+    /// Number of times the vector fired since boot.
+    pub count: u64,
+    /// Tick at which the vector last fired.
+    pub last_seen: u64,
+}
+
+impl fmt::Display for VectorStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>3}: {:>10} {:>10}  {}",
+            self.vector, self.count, self.last_seen, self.name
+        )
+    }
+}
+
+/// Statistics of every interrupt vector that fired at least once.
+#[must_use]
+pub fn interrupt_stats() -> Vec<VectorStats> {
+    (0..=u8::MAX)
+        .zip(COUNTS.iter().zip(LAST_SEEN.iter()))
+        .filter_map(|(vector, (count, last_seen))| {
+            let count = count.load(Ordering::Relaxed);
+            (count > 0).then(|| VectorStats {
+                vector,
+                name: vector_name(vector),
+                count,
+                last_seen: last_seen.load(Ordering::Relaxed),
+            })
+        })
+        .collect()
+}
+
+/// The timer keeps firing, and shows up in the statistics.
+#[test_case]
+fn timer_is_counted() {
+    use super::pic::InterruptIndex;
+    use x86_64::instructions::hlt;
+
+    let timer = InterruptIndex::Timer.as_u8();
+    let before = COUNTS[usize::from(timer)].load(Ordering::Relaxed);
+    let start = pit::ticks();
+    while pit::ticks() < start + 2 {
+        hlt();
+    }
+    let stats = interrupt_stats();
+    let timer_stats = stats
+        .iter()
+        .find(|stats| stats.vector == timer)
+        .expect("timer missing from statistics");
+    assert!(timer_stats.count > before, "timer interrupts not counted");
+    assert_eq!(timer_stats.name, "timer", "wrong vector name");
+}
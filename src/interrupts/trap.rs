@@ -0,0 +1,180 @@
+// File: src/interrupts/trap.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::fmt;
+
+use x86_64::registers::rflags::RFlags;
+
+/// Complete register state of the code interrupted by a trap.
+///
+/// Built on the stack by the entry points generated with [`trap_entry!`]:
+/// the general purpose registers pushed by the entry point come first, then
+/// the frame pushed by the CPU. Handlers may modify it, the registers are
+/// restored from it when returning from the trap.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct TrapFrame {
+    /// `r15` register.
+    pub r15: u64,
+    /// `r14` register.
+    pub r14: u64,
+    /// `r13` register.
+    pub r13: u64,
+    /// `r12` register.
+    pub r12: u64,
+    /// `r11` register.
+    pub r11: u64,
+    /// `r10` register.
+    pub r10: u64,
+    /// `r9` register.
+    pub r9: u64,
+    /// `r8` register.
+    pub r8: u64,
+    /// `rbp` register.
+    pub rbp: u64,
+    /// `rdi` register.
+    pub rdi: u64,
+    /// `rsi` register.
+    pub rsi: u64,
+    /// `rdx` register.
+    pub rdx: u64,
+    /// `rcx` register.
+    pub rcx: u64,
+    /// `rbx` register.
+    pub rbx: u64,
+    /// `rax` register.
+    pub rax: u64,
+    /// Instruction pointer.
+    pub rip: u64,
+    /// Code segment selector.
+    pub cs: u64,
+    /// Flags register.
+    pub rflags: u64,
+    /// Stack pointer.
+    pub rsp: u64,
+    /// Stack segment selector.
+    pub ss: u64,
+}
+
+impl TrapFrame {
+    /// Flags register of the interrupted code.
+    #[must_use]
+    pub const fn flags(&self) -> RFlags {
+        RFlags::from_bits_truncate(self.rflags)
+    }
+
+    /// Sets or clears the trap flag, raising a debug exception after the
+    /// next instruction.
+    ///
+    /// # Parameters
+    /// * `enabled` - Whether to single step.
+    pub fn set_single_step(&mut self, enabled: bool) {
+        let mut flags = self.flags();
+        flags.set(RFlags::TRAP_FLAG, enabled);
+        self.rflags = flags.bits();
+    }
+}
+
+impl fmt::Display for TrapFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "rip: {:#018x} rsp: {:#018x} rflags: {:#x}",
+            self.rip, self.rsp, self.rflags
+        )?;
+        writeln!(
+            f,
+            "rax: {:#018x} rbx: {:#018x} rcx: {:#018x}",
+            self.rax, self.rbx, self.rcx
+        )?;
+        writeln!(
+            f,
+            "rdx: {:#018x} rsi: {:#018x} rdi: {:#018x}",
+            self.rdx, self.rsi, self.rdi
+        )?;
+        write!(
+            f,
+            "rbp: {:#018x} cs: {:#x} ss: {:#x}",
+            self.rbp, self.cs, self.ss
+        )
+    }
+}
+
+/// Defines an interrupt entry point saving every general purpose register
+/// into a [`TrapFrame`] before calling `$handler`.
+///
+/// The handler must be an `extern "C" fn(&mut TrapFrame)`. Only vectors
+/// without an error code are supported.
+macro_rules! trap_entry {
+    ($name:ident => $handler:path) => {
+        #[unsafe(naked)]
+        pub(super) extern "C" fn $name() {
+            core::arch::naked_asm!(
+                "push rax",
+                "push rbx",
+                "push rcx",
+                "push rdx",
+                "push rsi",
+                "push rdi",
+                "push rbp",
+                "push r8",
+                "push r9",
+                "push r10",
+                "push r11",
+                "push r12",
+                "push r13",
+                "push r14",
+                "push r15",
+                // 15 registers on top of the 5 words of the CPU frame:
+                // the stack is 16 bytes aligned for the call.
+                "mov rdi, rsp",
+                "cld",
+                "call {handler}",
+                "pop r15",
+                "pop r14",
+                "pop r13",
+                "pop r12",
+                "pop r11",
+                "pop r10",
+                "pop r9",
+                "pop r8",
+                "pop rbp",
+                "pop rdi",
+                "pop rsi",
+                "pop rdx",
+                "pop rcx",
+                "pop rbx",
+                "pop rax",
+                "iretq",
+                handler = sym $handler,
+            );
+        }
+    };
+}
+
+pub(super) use trap_entry;
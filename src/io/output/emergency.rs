@@ -0,0 +1,73 @@
+// File: src/io/output/emergency.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+// Panic messages must always get out: code may panic (or fault) while holding
+// the screen or serial lock, in which case the usual macros would spin
+// forever. These functions use the locked outputs when they are free, and
+// bypass the locks otherwise.
+
+use core::fmt;
+
+use super::{serial, vga};
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    vga::emergency_write(args);
+    serial::emergency_write(args);
+}
+
+#[doc(hidden)]
+pub fn _serial_print(args: fmt::Arguments) {
+    serial::emergency_write(args);
+}
+
+/// Prints to the screen and the serial interface without waiting for their
+/// locks, appending a newline.
+#[macro_export]
+macro_rules! emergency_println {
+    () => ($crate::io::output::emergency::_print(format_args!("\n")));
+    ($($arg:tt)*) => ($crate::io::output::emergency::_print(
+        format_args!("{}\n", format_args!($($arg)*))));
+}
+
+/// Prints to the serial interface without waiting for its lock, appending a
+/// newline.
+#[macro_export]
+macro_rules! emergency_serial_println {
+    () => ($crate::io::output::emergency::_serial_print(format_args!("\n")));
+    ($($arg:tt)*) => ($crate::io::output::emergency::_serial_print(
+        format_args!("{}\n", format_args!($($arg)*))));
+}
+
+/// Emergency output is written even while the screen is locked.
+#[test_case]
+fn output_while_locked() {
+    let _writer = vga::WRITER.lock();
+    let _serial = serial::SERIAL1.lock();
+    emergency_println!("emergency output while the outputs are locked");
+}
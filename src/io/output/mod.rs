@@ -1,3 +1,5 @@
+/// Output that never waits for a lock, for the panic handlers.
+pub mod emergency;
 /// Write to the first serial port.
 pub mod serial;
 /// VGA display.
@@ -26,22 +26,24 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use core::fmt;
+
 use lazy_static::lazy_static;
-use spin::Mutex;
 use uart_16550::SerialPort;
-use x86_64::instructions::interrupts;
+
+use crate::spinlock::IrqSpinlock;
 
 // Port adress of the first serial interface
 const SERIAL_PORT: u16 = 0x3F8;
 
 lazy_static! {
     /// First serial port.
-    pub static ref SERIAL1: Mutex<SerialPort> = {
+    pub static ref SERIAL1: IrqSpinlock<SerialPort> = {
         // SAFETY:
         // Port-Mapping IO
         let mut serial_port = unsafe { SerialPort::new(SERIAL_PORT) };
         serial_port.init();
-        Mutex::new(serial_port)
+        IrqSpinlock::new(serial_port)
     };
 }
 
@@ -50,12 +52,27 @@ lazy_static! {
 #[doc(hidden)]
 pub fn _print(args: ::core::fmt::Arguments) {
     use core::fmt::Write;
-    interrupts::without_interrupts(|| {
-        SERIAL1
-            .lock()
-            .write_fmt(args)
-            .expect("Printing to serial failed");
-    });
+    SERIAL1
+        .lock()
+        .write_fmt(args)
+        .expect("Printing to serial failed");
+}
+
+/// Writes to the serial port even if [`SERIAL1`] is held, for the panic
+/// handlers.
+///
+/// Uses the locked port if it is free, or else writes straight to the
+/// hardware (the port is already initialized).
+pub(super) fn emergency_write(args: fmt::Arguments) {
+    use core::fmt::Write;
+    if let Some(mut port) = SERIAL1.try_lock() {
+        port.write_fmt(args).ok();
+        return;
+    }
+    // SAFETY:
+    // Port-mapped IO, the output may only be interleaved with the holder's.
+    let mut port = unsafe { SerialPort::new(SERIAL_PORT) };
+    port.write_fmt(args).ok();
 }
 
 /// Prints to the host through the serial interface.
@@ -29,9 +29,9 @@
 use core::{array::from_fn, fmt};
 
 use lazy_static::lazy_static;
-use spin::Mutex;
 use volatile::Volatile;
-use x86_64::instructions::interrupts;
+
+use crate::spinlock::IrqSpinlock;
 
 const VGA_MMIO: u64 = 0xb8000;
 
@@ -39,8 +39,8 @@ lazy_static! {
     /// Static text writer to the screen.
     ///
     /// [`lazy_static`] is necessary here to allow the mut Buffer,
-    /// and [`spin::Mutex`] to allow for the writer to be mutable.
-    pub static ref WRITER: Mutex<Writer> = Mutex::new(Writer {
+    /// and [`IrqSpinlock`] to allow for the writer to be mutable.
+    pub static ref WRITER: IrqSpinlock<Writer> = IrqSpinlock::new(Writer {
         column_position: 0,
         color_code: ColorCode::new(Color::Black, Color::Green),
         // SAFETY:
@@ -231,9 +231,29 @@ macro_rules! println {
 pub fn _print(args: fmt::Arguments) {
     use core::fmt::Write;
     #[expect(clippy::unwrap_used)]
-    interrupts::without_interrupts(|| {
-        WRITER.lock().write_fmt(args).unwrap();
-    });
+    WRITER.lock().write_fmt(args).unwrap();
+}
+
+/// Writes to the screen even if [`WRITER`] is held, for the panic handlers.
+///
+/// Uses the writer if it is free; otherwise writes straight to the VGA
+/// buffer on a new line, in white on red, with a writer of its own.
+pub(super) fn emergency_write(args: fmt::Arguments) {
+    use core::fmt::Write;
+    if let Some(mut writer) = WRITER.try_lock() {
+        writer.write_fmt(args).ok();
+        return;
+    }
+    let mut writer = Writer {
+        column_position: 0,
+        color_code: ColorCode::new(Color::White, Color::Red),
+        // SAFETY:
+        // MMIO mapping, the holder of the lock cannot be trusted anymore: the
+        // kernel is going down.
+        buffer: unsafe { &mut *(VGA_MMIO as *mut Buffer) },
+    };
+    writer.new_line();
+    writer.write_fmt(args).ok();
 }
 
 /// # Panics
@@ -243,18 +263,16 @@ fn direct_output() {
     use core::fmt::Write;
 
     let string = "Some test string that fits on a single line";
-    interrupts::without_interrupts(|| {
-        let mut writer = WRITER.lock();
-        writeln!(writer, "\n{string}").expect("writeln failed");
-        for (i, character) in string.chars().enumerate() {
-            let screen_char = writer.buffer.chars[BUFFER_HEIGHT - 2][i].read();
-            assert_eq!(
-                char::from(screen_char.ascii_character),
-                character,
-                "mismatch between characters"
-            );
-        }
-    });
+    let mut writer = WRITER.lock();
+    writeln!(writer, "\n{string}").expect("writeln failed");
+    for (i, character) in string.chars().enumerate() {
+        let screen_char = writer.buffer.chars[BUFFER_HEIGHT - 2][i].read();
+        assert_eq!(
+            char::from(screen_char.ascii_character),
+            character,
+            "mismatch between characters"
+        );
+    }
 }
 
 #[test_case]
@@ -47,23 +47,43 @@ use bootloader::entry_point;
 use bootloader::BootInfo;
 use x86_64::{instructions::hlt, VirtAddr};
 
+/// ACPI tables.
+pub mod acpi;
 /// Global heap allocator
 mod allocator;
+/// Debugging facilities.
+pub mod debug;
 /// CPU interrupts handling.
 mod interrupts;
 /// Paging handling.
 mod paging;
+/// Shutdown and reboot.
+pub mod power;
+/// Multiprocessor support.
+pub mod smp;
+/// Interrupt-safe spinlock.
+pub mod spinlock;
+/// System calls.
+pub mod syscall;
 /// Multitasking implementation.
 pub mod tasks;
 /// Test handlers.
 mod tests;
+/// Preemptive kernel threads.
+pub mod thread;
+/// User mode programs.
+pub mod user;
 
 /// I/O functionalities
 pub mod io;
 
 pub use allocator::{init as init_heap, HEAP_SIZE};
+pub use interrupts::{
+    irq,
+    stats::{interrupt_stats, VectorStats},
+};
 pub use paging::{init as init_paging, BootInfoFrameAllocator};
-pub use tests::test_runner;
+pub use tests::{test_runner, AsyncTest, AsyncTestFn};
 
 #[cfg(test)]
 entry_point!(test_kernel_main);
@@ -79,9 +99,16 @@ fn test_kernel_main(boot_info: &'static BootInfo) -> ! {
 /// Initializes the kernel.
 pub fn init(boot_info: &'static BootInfo) {
     interrupts::init();
+    #[cfg(feature = "gdb")]
+    debug::gdb::init();
+    syscall::init();
     let mut mapper = unsafe { init_paging(VirtAddr::new(boot_info.physical_memory_offset)) };
     let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
     init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    paging::install(mapper, frame_allocator);
+    thread::init();
+    interrupts::init_hardware();
+    smp::init();
 }
 
 /// Panic handler for tests.
@@ -89,8 +116,8 @@ pub fn init(boot_info: &'static BootInfo) {
 /// # Parameters
 /// * `info` - Information for the panic.
 pub fn test_panic_handler(info: &PanicInfo) -> ! {
-    serial_println!("\x1B[31m[failed]\x1B[0m\n");
-    serial_println!("{}", info);
+    emergency_serial_println!("\x1B[31m[failed]\x1B[0m\n");
+    emergency_serial_println!("{}", info);
     exit_qemu(QemuExitCode::Failed);
     hlt_loop();
 }
@@ -39,9 +39,11 @@ extern crate alloc;
 use alloc::{boxed::Box, rc::Rc, vec, vec::Vec};
 use bootloader::{entry_point, BootInfo};
 use core::{mem::drop, panic::PanicInfo};
+#[cfg(not(test))]
+use crysalis::{debug::backtrace::Backtrace, emergency_println};
 use crysalis::{
     hlt_loop, init, println,
-    tasks::{executor::Executor, keyboard, simple_executor::SimpleExecutor, Task},
+    tasks::{executor::Executor, keyboard, simple_executor::SimpleExecutor},
 };
 entry_point!(kernel_main);
 
@@ -90,8 +92,8 @@ fn kernel_main(boot_info: &'static BootInfo) -> ! {
     println!("all good!");
 
     let mut executor = Executor::default();
-    executor.spawn(Task::new(example_task()));
-    executor.spawn(Task::new(keyboard::print_keypresses()));
+    executor.spawn_named("example", example_task());
+    executor.spawn_named("keyboard", keyboard::print_keypresses());
     executor.run();
 }
 
@@ -99,7 +101,8 @@ fn kernel_main(boot_info: &'static BootInfo) -> ! {
 #[cfg(not(test))]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    println!("{info}");
+    emergency_println!("{info}");
+    Backtrace::capture().print();
     hlt_loop();
 }
 
@@ -1,10 +1,28 @@
+use alloc::vec::Vec;
 use bootloader::bootinfo::{MemoryMap, MemoryRegionType};
+use conquer_once::spin::OnceCell;
+use core::sync::atomic::{AtomicU64, Ordering};
 use x86_64::{
     registers::control::Cr3,
-    structures::paging::{FrameAllocator, OffsetPageTable, PageTable, PhysFrame, Size4KiB},
+    structures::paging::{
+        mapper::MapToError, page_table::FrameError, FrameAllocator, FrameDeallocator, Mapper,
+        OffsetPageTable, Page, PageSize, PageTable, PageTableFlags, PhysFrame, Size4KiB,
+    },
     PhysAddr, VirtAddr,
 };
 
+use crate::spinlock::IrqSpinlock;
+
+/// Virtual address at which the bootloader mapped the physical memory.
+static PHYSICAL_MEMORY_OFFSET: OnceCell<VirtAddr> = OnceCell::uninit();
+
+/// End of the low memory, reachable from real mode.
+const LOW_MEMORY_END: u64 = 0x10_0000;
+/// Start of the virtual addresses used to map devices memory.
+const MMIO_START: u64 = 0x0000_0900_0000_0000;
+/// Next free virtual address to map devices memory.
+static NEXT_MMIO: AtomicU64 = AtomicU64::new(MMIO_START);
+
 /// Initialize a new `OffsetPageTable`.
 ///
 /// # Safety
@@ -15,6 +33,7 @@ use x86_64::{
 /// to avoid aliasing `&mut` references (which is undefined behavior).
 #[must_use]
 pub unsafe fn init(physical_memory_offset: VirtAddr) -> OffsetPageTable<'static> {
+    PHYSICAL_MEMORY_OFFSET.init_once(|| physical_memory_offset);
     let level_4_table = active_level_4_table(physical_memory_offset);
     OffsetPageTable::new(level_4_table, physical_memory_offset)
 }
@@ -39,6 +58,147 @@ unsafe fn active_level_4_table(physical_memory_offset: VirtAddr) -> &'static mut
     }
 }
 
+/// Translates a virtual address to the physical address it is mapped to.
+///
+/// Walks the active page tables, returning `None` if the address is not
+/// mapped (or if paging was not initialized yet).
+///
+/// # Parameters
+/// * `addr` - The virtual address to translate.
+#[must_use]
+pub fn translate_addr(addr: VirtAddr) -> Option<PhysAddr> {
+    walk(addr).map(|(phys, _)| phys)
+}
+
+/// Returns the virtual address a physical address is mapped to, through the
+/// complete physical memory mapping.
+///
+/// Returns `None` if paging was not initialized yet.
+///
+/// # Parameters
+/// * `addr` - The physical address.
+#[must_use]
+pub fn phys_to_virt(addr: PhysAddr) -> Option<VirtAddr> {
+    PHYSICAL_MEMORY_OFFSET
+        .get()
+        .map(|offset| *offset + addr.as_u64())
+}
+
+/// Maps devices memory (uncached) at a new virtual address.
+///
+/// The physical memory mapping only covers RAM, devices registers are often
+/// above it.
+///
+/// # Parameters
+/// * `addr` - Physical address of the registers,
+/// * `size` - Size of the registers.
+///
+/// # Errors
+/// If the memory could not be mapped.
+pub fn map_mmio(addr: PhysAddr, size: u64) -> Result<VirtAddr, MapToError<Size4KiB>> {
+    let first = PhysFrame::<Size4KiB>::containing_address(addr);
+    let last = PhysFrame::<Size4KiB>::containing_address(addr + size.max(1) - 1_u64);
+    let frames = PhysFrame::range_inclusive(first, last);
+    let length = (last.start_address() - first.start_address()) + Size4KiB::SIZE;
+    let start = NEXT_MMIO.fetch_add(length, Ordering::Relaxed);
+    let first_page = Page::<Size4KiB>::containing_address(VirtAddr::new(start));
+    let flags = PageTableFlags::PRESENT
+        | PageTableFlags::WRITABLE
+        | PageTableFlags::NO_CACHE
+        | PageTableFlags::WRITE_THROUGH
+        | PageTableFlags::NO_EXECUTE;
+    with_memory(|mapper, frame_allocator| {
+        for (page, frame) in (0..).map(|index| first_page + index).zip(frames) {
+            // SAFETY:
+            // The pages are reserved for this mapping only.
+            unsafe {
+                mapper.map_to(page, frame, flags, frame_allocator)?.flush();
+            }
+        }
+        Ok(VirtAddr::new(start) + (addr - first.start_address()))
+    })
+    .unwrap_or(Err(MapToError::FrameAllocationFailed))
+}
+
+/// Whether a virtual address is mapped and accessible from user mode.
+///
+/// # Parameters
+/// * `addr` - The virtual address to check.
+#[must_use]
+pub fn is_user_accessible(addr: VirtAddr) -> bool {
+    walk(addr).is_some_and(|(_, flags)| flags.contains(PageTableFlags::USER_ACCESSIBLE))
+}
+
+/// Walks the active page tables for a virtual address.
+///
+/// Returns the physical address, and the flags common to every level of the
+/// page tables.
+///
+/// # Parameters
+/// * `addr` - The virtual address to translate.
+fn walk(addr: VirtAddr) -> Option<(PhysAddr, PageTableFlags)> {
+    let physical_memory_offset = *PHYSICAL_MEMORY_OFFSET.get()?;
+    let (level_4_table_frame, _) = Cr3::read();
+    let indexes = [
+        addr.p4_index(),
+        addr.p3_index(),
+        addr.p2_index(),
+        addr.p1_index(),
+    ];
+
+    let mut frame = level_4_table_frame;
+    let mut flags = PageTableFlags::all();
+    for (level, index) in indexes.into_iter().enumerate() {
+        let virt = physical_memory_offset + frame.start_address().as_u64();
+        let table_ptr: *const PageTable = virt.as_ptr();
+        // SAFETY:
+        // The complete physical memory is mapped at the offset, and the frame
+        // comes from the active page tables.
+        let table = unsafe { &*table_ptr };
+        let entry = &table[index];
+        flags &= entry.flags();
+        frame = match entry.frame() {
+            Ok(next) => next,
+            Err(FrameError::FrameNotPresent) => return None,
+            Err(FrameError::HugeFrame) => {
+                // 1 GiB page at level 3, 2 MiB page at level 2
+                let page_size = if level == 1 { 1 << 30 } else { 1 << 21 };
+                return Some((entry.addr() + (addr.as_u64() & (page_size - 1)), flags));
+            }
+        };
+    }
+    Some((frame.start_address() + u64::from(addr.page_offset()), flags))
+}
+
+/// Active page tables and frame allocator, once the kernel is initialized.
+static MEMORY: IrqSpinlock<Option<(OffsetPageTable<'static>, BootInfoFrameAllocator)>> =
+    IrqSpinlock::new(None);
+
+/// Makes the page tables and frame allocator available to the rest of the
+/// kernel, through [`with_memory`].
+///
+/// # Parameters
+/// * `mapper` - The active page tables,
+/// * `frame_allocator` - The frame allocator.
+pub fn install(mapper: OffsetPageTable<'static>, frame_allocator: BootInfoFrameAllocator) {
+    *MEMORY.lock() = Some((mapper, frame_allocator));
+}
+
+/// Runs a function with the page tables and frame allocator.
+///
+/// Returns `None` if they were not installed yet.
+///
+/// # Parameters
+/// * `function` - The function to run.
+pub fn with_memory<F, R>(function: F) -> Option<R>
+where
+    F: FnOnce(&mut OffsetPageTable<'static>, &mut BootInfoFrameAllocator) -> R,
+{
+    let mut memory = MEMORY.lock();
+    let (mapper, frame_allocator) = memory.as_mut()?;
+    Some(function(mapper, frame_allocator))
+}
+
 // /// Creates an example mapping for the given page to frame `0xb8000`.
 // pub fn create_example_mapping<F>(page: Page, mapper: &mut OffsetPageTable, frame_allocator: &mut F)
 // where
@@ -55,9 +215,12 @@ unsafe fn active_level_4_table(physical_memory_offset: VirtAddr) -> &'static mut
 // }
 
 /// A `FrameAllocator` that returns usable frames from the bootloader's memory map.
+///
+/// Deallocated frames are handed out again before new ones.
 pub struct BootInfoFrameAllocator {
     memory_map: &'static MemoryMap,
     next: usize,
+    recycled: Vec<PhysFrame>,
 }
 
 impl BootInfoFrameAllocator {
@@ -71,9 +234,24 @@ impl BootInfoFrameAllocator {
             .map(|region| region.range.start_addr()..region.range.end_addr())
             // transform to an iterator of frame start addresses
             .flat_map(|region| region.step_by(4096))
+            // keep the first MiB for real mode code (see `low_memory_frame`)
+            .filter(|&addr| addr >= LOW_MEMORY_END)
             // create `PhysFrame` types from the start addresses
             .map(|addr| PhysFrame::containing_address(PhysAddr::new(addr)))
     }
+
+    /// Returns a usable frame below 1 MiB, for code run in real mode.
+    ///
+    /// Those frames are never handed out by the allocator.
+    #[must_use]
+    pub fn low_memory_frame(&self) -> Option<PhysFrame> {
+        self.memory_map
+            .iter()
+            .filter(|region| region.region_type == MemoryRegionType::Usable)
+            .map(|region| region.range.start_addr().max(Size4KiB::SIZE))
+            .find(|&addr| addr + Size4KiB::SIZE <= LOW_MEMORY_END)
+            .map(|addr| PhysFrame::containing_address(PhysAddr::new(addr)))
+    }
 }
 
 impl BootInfoFrameAllocator {
@@ -89,14 +267,24 @@ impl BootInfoFrameAllocator {
         Self {
             memory_map,
             next: 0,
+            recycled: Vec::new(),
         }
     }
 }
 
 unsafe impl FrameAllocator<Size4KiB> for BootInfoFrameAllocator {
     fn allocate_frame(&mut self) -> Option<PhysFrame> {
+        if let Some(frame) = self.recycled.pop() {
+            return Some(frame);
+        }
         let frame = self.usable_frames().nth(self.next);
         self.next += 1;
         frame
     }
 }
+
+impl FrameDeallocator<Size4KiB> for BootInfoFrameAllocator {
+    unsafe fn deallocate_frame(&mut self, frame: PhysFrame) {
+        self.recycled.push(frame);
+    }
+}
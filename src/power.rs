@@ -0,0 +1,323 @@
+// File: src/power.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{hint, ptr};
+
+use x86_64::{
+    instructions::{interrupts, port::Port, tables::lidt},
+    structures::DescriptorTablePointer,
+    PhysAddr, VirtAddr,
+};
+
+use crate::{
+    acpi::{AddressSpace, Fadt, GenericAddress},
+    hlt_loop,
+    paging::map_mmio,
+    println,
+};
+
+/// AML `NameOp`, introducing a named object.
+const AML_NAME_OP: u8 = 0x08;
+/// AML `PackageOp`.
+const AML_PACKAGE_OP: u8 = 0x12;
+/// AML `BytePrefix`, introducing a byte constant.
+const AML_BYTE_PREFIX: u8 = 0x0A;
+
+/// ACPI is enabled: power management events are delivered as SCIs.
+const SCI_ENABLE: u64 = 1;
+/// Sleep enable bit of the PM1 control registers.
+const SLEEP_ENABLE: u64 = 1 << 13;
+/// Shift of the sleep type in the PM1 control registers.
+const SLEEP_TYPE_SHIFT: u32 = 10;
+
+/// Power off ports of the emulators, with the value to write.
+const EMULATOR_SHUTDOWN: [(u16, u16); 2] = [(0x604, 0x2000), (0xB004, 0x2000)];
+/// Status and command port of the 8042 keyboard controller.
+const KEYBOARD_CONTROLLER: u16 = 0x64;
+/// Input buffer full bit of the 8042 status.
+const KEYBOARD_INPUT_FULL: u8 = 1 << 1;
+/// 8042 command pulsing the processor reset line.
+const KEYBOARD_RESET: u8 = 0xFE;
+/// Iterations to wait for a device before giving up.
+const DEVICE_TIMEOUT: usize = 1_000_000;
+
+/// Turns the machine off.
+///
+/// Uses the ACPI S5 sleep state, or the power off ports of QEMU and Bochs.
+/// Halts forever if both fail.
+pub fn shutdown() -> ! {
+    interrupts::disable();
+    if let Some(fadt) = Fadt::get() {
+        acpi_shutdown(&fadt);
+    }
+    for (port, value) in EMULATOR_SHUTDOWN {
+        // SAFETY:
+        // Power off port of the emulators, unused on real hardware.
+        unsafe {
+            Port::new(port).write(value);
+        }
+    }
+    println!("It is now safe to turn off the computer.");
+    hlt_loop();
+}
+
+/// Restarts the machine.
+///
+/// Tries the 8042 reset line, then the ACPI reset register, and finally
+/// triple faults.
+pub fn reboot() -> ! {
+    interrupts::disable();
+    let fadt = Fadt::get();
+    if fadt.as_ref().is_none_or(|fadt| fadt.has_8042) {
+        keyboard_reset();
+        wait();
+    }
+    if let Some((register, value)) = fadt.and_then(|fadt| fadt.reset) {
+        // SAFETY:
+        // The firmware describes this register as the reset register.
+        unsafe {
+            write_register(&register, u64::from(value));
+        }
+        wait();
+    }
+    triple_fault();
+}
+
+/// Waits for a device to act.
+fn wait() {
+    for _ in 0..DEVICE_TIMEOUT {
+        hint::spin_loop();
+    }
+}
+
+/// Enters the S5 sleep state through the PM1 control registers.
+///
+/// Returns if the state cannot be entered.
+fn acpi_shutdown(fadt: &Fadt) {
+    let Some(pm1a_control) = fadt.pm1a_control else {
+        return;
+    };
+    let Some((sleep_type_a, sleep_type_b)) = fadt.dsdt_table().and_then(s5_sleep_types) else {
+        return;
+    };
+    enable_acpi(fadt, &pm1a_control);
+    // SAFETY:
+    // Entering a sleep state through the registers given by the firmware.
+    unsafe {
+        write_register(
+            &pm1a_control,
+            u64::from(sleep_type_a) << SLEEP_TYPE_SHIFT | SLEEP_ENABLE,
+        );
+        if let Some(pm1b_control) = fadt.pm1b_control {
+            write_register(
+                &pm1b_control,
+                u64::from(sleep_type_b) << SLEEP_TYPE_SHIFT | SLEEP_ENABLE,
+            );
+        }
+    }
+    wait();
+}
+
+/// Switches the chipset to ACPI mode, if the firmware left it in legacy mode.
+fn enable_acpi(fadt: &Fadt, pm1a_control: &GenericAddress) {
+    let is_enabled = || read_register(pm1a_control).is_none_or(|value| value & SCI_ENABLE != 0);
+    if is_enabled() || fadt.acpi_enable == 0 {
+        return;
+    }
+    let Ok(port) = u16::try_from(fadt.smi_command) else {
+        return;
+    };
+    // SAFETY:
+    // The FADT gives the command enabling ACPI.
+    unsafe {
+        Port::new(port).write(fadt.acpi_enable);
+    }
+    for _ in 0..DEVICE_TIMEOUT {
+        if is_enabled() {
+            return;
+        }
+        hint::spin_loop();
+    }
+}
+
+/// Finds the sleep types of the `\_S5` package of the DSDT.
+///
+/// Returns the values for the `PM1a` and `PM1b` control registers.
+///
+/// # Parameters
+/// * `dsdt` - Content of the DSDT.
+fn s5_sleep_types(dsdt: &[u8]) -> Option<(u8, u8)> {
+    let position = dsdt.windows(4).enumerate().position(|(index, name)| {
+        name == b"_S5_"
+            && index
+                .checked_sub(1)
+                .and_then(|before| dsdt.get(before))
+                .is_some_and(|&op| op == AML_NAME_OP || op == b'\\')
+    })?;
+    let package = dsdt.get(position + 4..)?;
+    let [AML_PACKAGE_OP, length, ..] = *package else {
+        return None;
+    };
+    // the package length is followed by the number of elements
+    let mut elements = package.get(2 + usize::from(length >> 6) + 1..)?;
+    let mut next = || {
+        let value = match *elements {
+            [AML_BYTE_PREFIX, value, ..] => {
+                elements = &elements[2..];
+                value
+            }
+            [value, ..] => {
+                elements = &elements[1..];
+                value
+            }
+            [] => return None,
+        };
+        Some(value)
+    };
+    Some((next()?, next()?))
+}
+
+/// Reads an I/O register.
+///
+/// Returns `None` for other address spaces.
+fn read_register(register: &GenericAddress) -> Option<u64> {
+    if register.address_space != AddressSpace::SystemIo {
+        return None;
+    }
+    let port = u16::try_from(register.address).ok()?;
+    // SAFETY:
+    // Reading a register described by the firmware.
+    let value = unsafe {
+        match register.bit_width {
+            8 => u64::from(Port::<u8>::new(port).read()),
+            32 => u64::from(Port::<u32>::new(port).read()),
+            _ => u64::from(Port::<u16>::new(port).read()),
+        }
+    };
+    Some(value)
+}
+
+/// Writes to a register in memory or I/O space.
+///
+/// # Parameters
+/// * `register` - The register,
+/// * `value` - The value, truncated to the register width.
+///
+/// # Safety
+/// The write must not break memory safety.
+#[expect(clippy::cast_possible_truncation)]
+unsafe fn write_register(register: &GenericAddress, value: u64) {
+    match register.address_space {
+        AddressSpace::SystemIo => {
+            let Ok(port) = u16::try_from(register.address) else {
+                return;
+            };
+            // SAFETY:
+            // Guaranteed by the caller.
+            unsafe {
+                match register.bit_width {
+                    8 => Port::new(port).write(value as u8),
+                    32 => Port::new(port).write(value as u32),
+                    _ => Port::new(port).write(value as u16),
+                }
+            }
+        }
+        AddressSpace::SystemMemory => {
+            let Ok(addr) = map_mmio(PhysAddr::new(register.address), 8) else {
+                return;
+            };
+            // SAFETY:
+            // Guaranteed by the caller, the register is mapped.
+            unsafe {
+                match register.bit_width {
+                    8 => ptr::write_volatile(addr.as_mut_ptr(), value as u8),
+                    16 => ptr::write_volatile(addr.as_mut_ptr(), value as u16),
+                    64 => ptr::write_volatile(addr.as_mut_ptr(), value),
+                    _ => ptr::write_volatile(addr.as_mut_ptr(), value as u32),
+                }
+            }
+        }
+        AddressSpace::PciConfiguration | AddressSpace::Other(_) => {}
+    }
+}
+
+/// Pulses the processor reset line through the 8042 keyboard controller.
+fn keyboard_reset() {
+    let mut port = Port::<u8>::new(KEYBOARD_CONTROLLER);
+    for _ in 0..DEVICE_TIMEOUT {
+        // SAFETY:
+        // Reading the status of the keyboard controller.
+        if unsafe { port.read() } & KEYBOARD_INPUT_FULL == 0 {
+            break;
+        }
+        hint::spin_loop();
+    }
+    // SAFETY:
+    // The machine is being restarted.
+    unsafe {
+        port.write(KEYBOARD_RESET);
+    }
+}
+
+/// Resets the processor with an exception it cannot handle.
+fn triple_fault() -> ! {
+    let empty = DescriptorTablePointer {
+        limit: 0,
+        base: VirtAddr::zero(),
+    };
+    // SAFETY:
+    // The machine is being restarted: the next exception triple faults.
+    unsafe {
+        lidt(&empty);
+    }
+    interrupts::int3();
+    hlt_loop();
+}
+
+/// The sleep types are read from the `\_S5` package, as QEMU encodes it.
+#[test_case]
+fn s5_package_is_parsed() {
+    let dsdt = [
+        0x10,
+        AML_NAME_OP,
+        b'_',
+        b'S',
+        b'5',
+        b'_',
+        AML_PACKAGE_OP,
+        0x0A,
+        0x04,
+        AML_BYTE_PREFIX,
+        0x05,
+        0x01,
+        0x00,
+        0x00,
+    ];
+    assert_eq!(s5_sleep_types(&dsdt), Some((5, 1)));
+    assert_eq!(s5_sleep_types(b"_S5_"), None);
+}
@@ -0,0 +1,218 @@
+// File: src/smp/mod.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec;
+use core::{
+    slice,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use x86_64::{
+    instructions::interrupts,
+    registers::control::Cr3,
+    structures::paging::{mapper::MapToError, Mapper, Page, PageTableFlags, PhysFrame, Size4KiB},
+    PhysAddr, VirtAddr,
+};
+
+use crate::{
+    acpi::Madt,
+    hlt_loop,
+    interrupts::{self as cpu_interrupts, lapic},
+    paging::{phys_to_virt, with_memory},
+    println,
+    tasks::timer::{duration_to_ticks, ticks},
+    BootInfoFrameAllocator,
+};
+
+/// Real mode startup code of the application processors.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+mod trampoline;
+
+/// Size of the stack of each application processor, in 64 bits words.
+const AP_STACK_WORDS: usize = 8 * 1024;
+/// Ticks to wait for an application processor to come online.
+const STARTUP_TIMEOUT: u64 = 10;
+/// Delay between the INIT and the first STARTUP interrupt.
+const INIT_DELAY: Duration = Duration::from_millis(10);
+/// Delay after each STARTUP interrupt.
+const STARTUP_DELAY: Duration = Duration::from_micros(200);
+
+/// Number of processors online, the bootstrap processor included.
+static CPUS_ONLINE: AtomicUsize = AtomicUsize::new(1);
+
+/// Number of processors online, the bootstrap processor included.
+#[must_use]
+pub fn cpus_online() -> usize {
+    CPUS_ONLINE.load(Ordering::Acquire)
+}
+
+/// Starts every application processor listed in the MADT.
+///
+/// They are parked in their idle loop; the kernel keeps running on the
+/// bootstrap processor. Interrupts must be enabled, to measure the delays
+/// of the startup sequence.
+pub fn init() {
+    let Some(madt) = Madt::get() else {
+        println!("SMP: no MADT, running on a single processor");
+        return;
+    };
+    if let Err(error) = lapic::init(PhysAddr::new(madt.local_apic_address)) {
+        println!("SMP: cannot map the local APIC: {error:?}");
+        return;
+    }
+    let bootstrap = lapic::id();
+    let frame = match with_memory(|mapper, frame_allocator| {
+        let frame = frame_allocator
+            .low_memory_frame()
+            .ok_or(MapToError::FrameAllocationFailed)?;
+        identity_map(mapper, frame_allocator, frame).map(|()| frame)
+    }) {
+        Some(Ok(frame)) => frame,
+        Some(Err(error)) => {
+            println!("SMP: cannot map the startup code: {error:?}");
+            return;
+        }
+        None => return,
+    };
+
+    for processor in &madt.processors {
+        if processor.usable
+            && Some(processor.apic_id) != bootstrap
+            && !start(frame, processor.apic_id)
+        {
+            println!("SMP: processor {} did not start", processor.apic_id);
+        }
+    }
+    println!(
+        "SMP: {} of {} processors online",
+        cpus_online(),
+        madt.processors.len()
+    );
+}
+
+/// Maps the trampoline frame at its own address, so that it keeps running
+/// once the processor enables paging.
+fn identity_map(
+    mapper: &mut impl Mapper<Size4KiB>,
+    frame_allocator: &mut BootInfoFrameAllocator,
+    frame: PhysFrame,
+) -> Result<(), MapToError<Size4KiB>> {
+    let page = Page::containing_address(VirtAddr::new(frame.start_address().as_u64()));
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
+    // SAFETY:
+    // The low memory is not used by the kernel.
+    match unsafe { mapper.map_to(page, frame, flags, frame_allocator) } {
+        Ok(flush) => {
+            flush.flush();
+            Ok(())
+        }
+        Err(MapToError::PageAlreadyMapped(existing)) if existing == frame => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// Waits for at least the given duration.
+///
+/// # Parameters
+/// * `duration` - How long to wait for.
+fn wait(duration: Duration) {
+    // the current tick may be about to end: it does not count
+    let deadline = ticks() + duration_to_ticks(duration) + 1;
+    while ticks() < deadline {
+        interrupts::enable_and_hlt();
+    }
+}
+
+/// Starts an application processor, with the INIT-SIPI-SIPI sequence.
+///
+/// Returns whether it came online.
+///
+/// # Parameters
+/// * `frame` - Frame holding the trampoline, below 1 MiB,
+/// * `apic_id` - Local APIC of the processor.
+fn start(frame: PhysFrame, apic_id: u8) -> bool {
+    let stack = vec![0_u64; AP_STACK_WORDS].leak();
+    // keep the stack aligned for the call to the entry point
+    let stack_top = stack.as_ptr_range().end as u64 & !0xF;
+    let base = frame.start_address().as_u64();
+    let Ok(vector) = u8::try_from(base >> 12) else {
+        return false;
+    };
+    let Some(copy) = phys_to_virt(frame.start_address()) else {
+        return false;
+    };
+    let code = trampoline::code();
+    // SAFETY:
+    // The frame is reserved for the trampoline, and mapped with the physical
+    // memory.
+    let copy = unsafe { slice::from_raw_parts_mut(copy.as_mut_ptr::<u8>(), code.len()) };
+    copy.copy_from_slice(code);
+    trampoline::set_parameters(
+        copy,
+        &trampoline::Parameters {
+            #[expect(clippy::cast_possible_truncation)]
+            base: base as u32,
+            page_table: Cr3::read().0.start_address().as_u64(),
+            stack: stack_top,
+            entry: VirtAddr::from_ptr(ap_main as *const ()).as_u64(),
+            argument: u64::from(apic_id),
+        },
+    );
+
+    let online = cpus_online();
+    lapic::send_init(apic_id);
+    wait(INIT_DELAY);
+    for _ in 0..2 {
+        lapic::send_startup(apic_id, vector);
+        wait(STARTUP_DELAY);
+        if cpus_online() > online {
+            return true;
+        }
+    }
+    let deadline = ticks() + STARTUP_TIMEOUT;
+    while ticks() < deadline {
+        if cpus_online() > online {
+            return true;
+        }
+        interrupts::enable_and_hlt();
+    }
+    false
+}
+
+/// Entry point of the application processors, in long mode.
+///
+/// # Parameters
+/// * `apic_id` - Local APIC of the processor.
+extern "C" fn ap_main(apic_id: u64) -> ! {
+    cpu_interrupts::init_ap();
+    CPUS_ONLINE.fetch_add(1, Ordering::AcqRel);
+    println!("SMP: processor {apic_id} online");
+    interrupts::enable();
+    hlt_loop();
+}
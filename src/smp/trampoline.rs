@@ -0,0 +1,197 @@
+// File: src/smp/trampoline.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{arch::global_asm, ptr::addr_of, slice};
+
+// Startup code of the application processors, copied below 1 MiB: they start
+// in real mode at the beginning of the page, go straight to long mode using
+// the kernel page tables, then call the entry point on their own stack.
+global_asm!(
+    ".section .rodata.ap_trampoline, \"a\"",
+    ".code16",
+    ".global AP_TRAMPOLINE_START",
+    "AP_TRAMPOLINE_START:",
+    "cli",
+    "cld",
+    "mov ax, cs",
+    "mov ds, ax",
+    "lgdt [ap_trampoline_gdt_pointer_offset]",
+    // physical address extension
+    "mov eax, cr4",
+    "or eax, 1 << 5",
+    "mov cr4, eax",
+    "mov eax, [ap_trampoline_cr3_offset]",
+    "mov cr3, eax",
+    // long mode and no-execute pages
+    "mov ecx, 0xC0000080",
+    "rdmsr",
+    "or eax, (1 << 8) | (1 << 11)",
+    "wrmsr",
+    // paging, write protection and protected mode, caches enabled
+    "mov eax, 0x80010011",
+    "mov cr0, eax",
+    // far jump to the 64 bits code segment (target patched at runtime)
+    ".byte 0x66, 0xEA",
+    ".global AP_TRAMPOLINE_JUMP",
+    "AP_TRAMPOLINE_JUMP:",
+    ".long 0",
+    ".word 0x08",
+    ".code64",
+    ".global AP_TRAMPOLINE_LONG_MODE",
+    "AP_TRAMPOLINE_LONG_MODE:",
+    "mov ax, 0x10",
+    "mov ds, ax",
+    "mov es, ax",
+    "mov ss, ax",
+    "mov rsp, [rip + AP_TRAMPOLINE_STACK]",
+    "mov rdi, [rip + AP_TRAMPOLINE_ARGUMENT]",
+    "call [rip + AP_TRAMPOLINE_ENTRY]",
+    "2:",
+    "hlt",
+    "jmp 2b",
+    ".balign 8",
+    ".global AP_TRAMPOLINE_GDT",
+    "AP_TRAMPOLINE_GDT:",
+    ".quad 0",
+    ".quad 0x00AF9A000000FFFF",
+    ".quad 0x00CF92000000FFFF",
+    ".global AP_TRAMPOLINE_GDT_POINTER",
+    "AP_TRAMPOLINE_GDT_POINTER:",
+    ".word 23",
+    ".long 0",
+    ".balign 8",
+    ".global AP_TRAMPOLINE_CR3",
+    "AP_TRAMPOLINE_CR3:",
+    ".quad 0",
+    ".global AP_TRAMPOLINE_STACK",
+    "AP_TRAMPOLINE_STACK:",
+    ".quad 0",
+    ".global AP_TRAMPOLINE_ENTRY",
+    "AP_TRAMPOLINE_ENTRY:",
+    ".quad 0",
+    ".global AP_TRAMPOLINE_ARGUMENT",
+    "AP_TRAMPOLINE_ARGUMENT:",
+    ".quad 0",
+    ".global AP_TRAMPOLINE_END",
+    "AP_TRAMPOLINE_END:",
+    // real mode addresses are relative to the start of the trampoline
+    ".set ap_trampoline_gdt_pointer_offset, AP_TRAMPOLINE_GDT_POINTER - AP_TRAMPOLINE_START",
+    ".set ap_trampoline_cr3_offset, AP_TRAMPOLINE_CR3 - AP_TRAMPOLINE_START",
+    ".previous",
+);
+
+extern "C" {
+    static AP_TRAMPOLINE_START: u8;
+    static AP_TRAMPOLINE_JUMP: u8;
+    static AP_TRAMPOLINE_LONG_MODE: u8;
+    static AP_TRAMPOLINE_GDT: u8;
+    static AP_TRAMPOLINE_GDT_POINTER: u8;
+    static AP_TRAMPOLINE_CR3: u8;
+    static AP_TRAMPOLINE_STACK: u8;
+    static AP_TRAMPOLINE_ENTRY: u8;
+    static AP_TRAMPOLINE_ARGUMENT: u8;
+    static AP_TRAMPOLINE_END: u8;
+}
+
+/// Offset of a symbol from the start of the trampoline.
+fn offset(symbol: *const u8) -> usize {
+    symbol as usize - addr_of!(AP_TRAMPOLINE_START) as usize
+}
+
+/// Machine code of the trampoline.
+pub(super) fn code() -> &'static [u8] {
+    let len = offset(addr_of!(AP_TRAMPOLINE_END));
+    // SAFETY:
+    // The trampoline lies between the two symbols.
+    unsafe { slice::from_raw_parts(addr_of!(AP_TRAMPOLINE_START), len) }
+}
+
+/// Parameters of the trampoline.
+pub(super) struct Parameters {
+    /// Physical (and identity mapped) address of the trampoline.
+    pub(super) base: u32,
+    /// Page tables of the processor.
+    pub(super) page_table: u64,
+    /// Top of the stack of the processor.
+    pub(super) stack: u64,
+    /// Entry point of the processor.
+    pub(super) entry: u64,
+    /// Argument given to the entry point.
+    pub(super) argument: u64,
+}
+
+/// Writes a little endian value in the trampoline copy.
+fn patch(copy: &mut [u8], offset: usize, value: u64, size: usize) {
+    for (index, byte) in copy[offset..offset + size].iter_mut().enumerate() {
+        #[expect(clippy::cast_possible_truncation)]
+        let shifted = (value >> (index * 8)) as u8;
+        *byte = shifted;
+    }
+}
+
+/// Fills the parameters of a copy of the trampoline.
+///
+/// # Parameters
+/// * `copy` - The copy of the trampoline,
+/// * `parameters` - Its parameters.
+pub(super) fn set_parameters(copy: &mut [u8], parameters: &Parameters) {
+    let base = u64::from(parameters.base);
+    let gdt = base + offset(addr_of!(AP_TRAMPOLINE_GDT)) as u64;
+    let long_mode = base + offset(addr_of!(AP_TRAMPOLINE_LONG_MODE)) as u64;
+    patch(
+        copy,
+        offset(addr_of!(AP_TRAMPOLINE_GDT_POINTER)) + 2,
+        gdt,
+        4,
+    );
+    patch(copy, offset(addr_of!(AP_TRAMPOLINE_JUMP)), long_mode, 4);
+    patch(
+        copy,
+        offset(addr_of!(AP_TRAMPOLINE_CR3)),
+        parameters.page_table,
+        8,
+    );
+    patch(
+        copy,
+        offset(addr_of!(AP_TRAMPOLINE_STACK)),
+        parameters.stack,
+        8,
+    );
+    patch(
+        copy,
+        offset(addr_of!(AP_TRAMPOLINE_ENTRY)),
+        parameters.entry,
+        8,
+    );
+    patch(
+        copy,
+        offset(addr_of!(AP_TRAMPOLINE_ARGUMENT)),
+        parameters.argument,
+        8,
+    );
+}
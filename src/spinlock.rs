@@ -0,0 +1,242 @@
+// File: src/spinlock.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    arch::x86_64::_rdtsc,
+    cell::UnsafeCell,
+    fmt, hint,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::atomic::{AtomicBool, AtomicU16, Ordering},
+};
+
+use x86_64::instructions::interrupts;
+
+use crate::{emergency_serial_println, interrupts::lapic};
+
+/// Hold time above which a warning is printed in debug builds, in cycles.
+const LONG_HOLD_CYCLES: u64 = 100_000_000;
+/// Owner of an unlocked lock.
+const NO_OWNER: u16 = 0;
+
+/// A spinlock disabling interrupts while it is held.
+///
+/// An interrupt handler taking a lock held by the code it interrupted would
+/// spin forever: the interrupt flag is cleared when the lock is taken, and
+/// restored when it is released. Debug builds also warn about locks held
+/// for long, and about locks taken again by the processor holding them.
+pub struct IrqSpinlock<T: ?Sized> {
+    /// Whether the lock is held.
+    locked: AtomicBool,
+    /// Processor holding the lock (local APIC identifier + 1).
+    owner: AtomicU16,
+    /// The protected data.
+    data: UnsafeCell<T>,
+}
+
+// SAFETY:
+// The lock gives exclusive access to the data.
+unsafe impl<T: ?Sized + Send> Sync for IrqSpinlock<T> {}
+// SAFETY:
+// The data is moved with the lock.
+unsafe impl<T: ?Sized + Send> Send for IrqSpinlock<T> {}
+
+impl<T> IrqSpinlock<T> {
+    /// Creates an unlocked lock.
+    ///
+    /// # Parameters
+    /// * `data` - The protected data.
+    pub const fn new(data: T) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            owner: AtomicU16::new(NO_OWNER),
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<T: ?Sized> IrqSpinlock<T> {
+    /// Takes the lock, spinning until it is available.
+    ///
+    /// Interrupts stay disabled until the guard is dropped.
+    #[track_caller]
+    pub fn lock(&self) -> IrqSpinlockGuard<'_, T> {
+        let were_enabled = interrupts::are_enabled();
+        interrupts::disable();
+        let cpu = current_cpu();
+        if cfg!(debug_assertions)
+            && self.locked.load(Ordering::Relaxed)
+            && self.owner.load(Ordering::Relaxed) == cpu
+        {
+            warn(format_args!(
+                "recursive lock taken at {} on the processor holding it",
+                Location::caller()
+            ));
+        }
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+        self.guard(cpu, were_enabled)
+    }
+
+    /// Takes the lock if it is available.
+    #[track_caller]
+    pub fn try_lock(&self) -> Option<IrqSpinlockGuard<'_, T>> {
+        let were_enabled = interrupts::are_enabled();
+        interrupts::disable();
+        if self
+            .locked
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            Some(self.guard(current_cpu(), were_enabled))
+        } else {
+            if were_enabled {
+                interrupts::enable();
+            }
+            None
+        }
+    }
+
+    /// Whether the lock is held.
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// Guard of the lock, once taken.
+    #[track_caller]
+    fn guard(&self, cpu: u16, were_enabled: bool) -> IrqSpinlockGuard<'_, T> {
+        self.owner.store(cpu, Ordering::Relaxed);
+        IrqSpinlockGuard {
+            lock: self,
+            were_enabled,
+            // SAFETY:
+            // Reading the time stamp counter has no side effect.
+            acquired: cfg!(debug_assertions).then(|| unsafe { _rdtsc() }),
+            location: Location::caller(),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for IrqSpinlock<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_lock() {
+            Some(guard) => formatter
+                .debug_struct("IrqSpinlock")
+                .field("data", &&*guard)
+                .finish(),
+            None => formatter.write_str("IrqSpinlock { <locked> }"),
+        }
+    }
+}
+
+impl<T: Default> Default for IrqSpinlock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Access to the data of a taken [`IrqSpinlock`], releasing it when dropped.
+pub struct IrqSpinlockGuard<'lock, T: ?Sized> {
+    /// The taken lock.
+    lock: &'lock IrqSpinlock<T>,
+    /// Whether interrupts were enabled before the lock was taken.
+    were_enabled: bool,
+    /// Time stamp counter when the lock was taken, in debug builds.
+    acquired: Option<u64>,
+    /// Where the lock was taken.
+    location: &'static Location<'static>,
+    /// Keeps the guard on the processor whose interrupt flag it restores,
+    /// and out of the automatic `Sync` implementation.
+    _not_send: PhantomData<*const ()>,
+}
+
+// SAFETY:
+// Sharing the guard only gives shared access to the data.
+unsafe impl<T: ?Sized + Sync> Sync for IrqSpinlockGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for IrqSpinlockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY:
+        // The lock is held.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for IrqSpinlockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY:
+        // The lock is held.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for IrqSpinlockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.owner.store(NO_OWNER, Ordering::Relaxed);
+        self.lock.locked.store(false, Ordering::Release);
+        if let Some(acquired) = self.acquired {
+            // SAFETY:
+            // Reading the time stamp counter has no side effect.
+            let held = unsafe { _rdtsc() }.wrapping_sub(acquired);
+            if held > LONG_HOLD_CYCLES {
+                warn(format_args!(
+                    "lock taken at {} held for {held} cycles",
+                    self.location
+                ));
+            }
+        }
+        if self.were_enabled {
+            interrupts::enable();
+        }
+    }
+}
+
+/// Processor running the code, as stored in the owner of a lock.
+fn current_cpu() -> u16 {
+    if cfg!(debug_assertions) {
+        lapic::id().map_or(1, |id| u16::from(id) + 1)
+    } else {
+        1
+    }
+}
+
+/// Prints a warning on the serial interface, bypassing its lock: it may be
+/// the faulty one.
+fn warn(args: fmt::Arguments<'_>) {
+    emergency_serial_println!("[lock] warning: {}", args);
+}
@@ -0,0 +1,152 @@
+// File: src/syscall/entry.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use super::syscall_handler;
+
+/// Top of the system call stack of the running thread, which the entry point
+/// switches to.
+///
+/// Set by the scheduler on every thread switch.
+static KERNEL_STACK_TOP: AtomicU64 = AtomicU64::new(0);
+
+/// Registers of the caller of a system call.
+///
+/// Built on the kernel stack by [`syscall_entry`], and restored from it when
+/// returning to the caller.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct SyscallFrame {
+    /// `r15` register.
+    pub r15: u64,
+    /// `r14` register.
+    pub r14: u64,
+    /// `r13` register.
+    pub r13: u64,
+    /// `r12` register.
+    pub r12: u64,
+    /// `r10` register, fourth argument.
+    pub r10: u64,
+    /// `r9` register, sixth argument.
+    pub r9: u64,
+    /// `r8` register, fifth argument.
+    pub r8: u64,
+    /// `rbp` register.
+    pub rbp: u64,
+    /// `rdi` register, first argument.
+    pub rdi: u64,
+    /// `rsi` register, second argument.
+    pub rsi: u64,
+    /// `rdx` register, third argument.
+    pub rdx: u64,
+    /// `rbx` register.
+    pub rbx: u64,
+    /// `rax` register, system call number then return value.
+    pub rax: u64,
+    /// Flags of the caller (saved by the CPU in `r11`).
+    pub rflags: u64,
+    /// Return address (saved by the CPU in `rcx`).
+    pub rip: u64,
+    /// Stack pointer of the caller.
+    pub rsp: u64,
+}
+
+impl SyscallFrame {
+    /// Number of the requested system call.
+    #[must_use]
+    pub const fn number(&self) -> u64 {
+        self.rax
+    }
+
+    /// Arguments of the system call, in order.
+    #[must_use]
+    pub const fn arguments(&self) -> [u64; 6] {
+        [self.rdi, self.rsi, self.rdx, self.r10, self.r8, self.r9]
+    }
+}
+
+/// Sets the stack system calls run on.
+///
+/// # Parameters
+/// * `top` - Top of the system call stack of the thread about to run.
+pub fn set_kernel_stack(top: u64) {
+    KERNEL_STACK_TOP.store(top, Ordering::Release);
+}
+
+/// Entry point of the `syscall` instruction.
+///
+/// Interrupts are masked on entry (see `SFMASK`), so no thread switch can
+/// happen while the stack top is swapped with the caller's stack pointer,
+/// which is then saved in the frame on the stack of the thread.
+#[unsafe(naked)]
+pub(super) extern "C" fn syscall_entry() {
+    core::arch::naked_asm!(
+        "xchg rsp, [rip + {kernel_stack}]",
+        "push [rip + {kernel_stack}]",
+        "mov [rip + {kernel_stack}], rsp",
+        "add qword ptr [rip + {kernel_stack}], 8",
+        "push rcx",
+        "push r11",
+        "push rax",
+        "push rbx",
+        "push rdx",
+        "push rsi",
+        "push rdi",
+        "push rbp",
+        "push r8",
+        "push r9",
+        "push r10",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+        "mov rdi, rsp",
+        "cld",
+        "call {handler}",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop r10",
+        "pop r9",
+        "pop r8",
+        "pop rbp",
+        "pop rdi",
+        "pop rsi",
+        "pop rdx",
+        "pop rbx",
+        "pop rax",
+        "pop r11",
+        "pop rcx",
+        "pop rsp",
+        "sysretq",
+        kernel_stack = sym KERNEL_STACK_TOP,
+        handler = sym syscall_handler,
+    );
+}
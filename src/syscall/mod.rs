@@ -0,0 +1,337 @@
+// File: src/syscall/mod.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{fmt, slice, str, time::Duration};
+
+use x86_64::{
+    registers::{
+        model_specific::{Efer, EferFlags, LStar, SFMask, Star},
+        rflags::RFlags,
+    },
+    structures::paging::{Page, Size4KiB},
+    PrivilegeLevel, VirtAddr,
+};
+
+use crate::{
+    hlt_loop,
+    interrupts::gdt,
+    paging::{is_user_accessible, translate_addr},
+    print, println, serial_print, thread, user,
+};
+
+pub(crate) use entry::set_kernel_stack;
+pub use entry::SyscallFrame;
+
+/// Entry point of the `syscall` instruction.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+mod entry;
+
+/// Numbers of the system calls, passed in `rax`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u64)]
+pub enum Syscall {
+    /// `write(fd, buffer, length)`: writes a UTF-8 buffer to the console.
+    ///
+    /// Descriptor 1 is the screen, descriptor 2 the serial interface.
+    /// Returns the number of bytes written.
+    Write = 0,
+    /// `exit(status)`: terminates the caller.
+    Exit = 1,
+    /// `yield()`: gives the CPU away.
+    Yield = 2,
+    /// `sleep(milliseconds)`: waits for the given duration.
+    Sleep = 3,
+}
+
+/// Errors returned by system calls.
+///
+/// They are returned in `rax` as the negated error code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u64)]
+pub enum SyscallError {
+    /// Unknown system call number.
+    UnknownSyscall = 1,
+    /// Invalid file descriptor.
+    BadDescriptor = 2,
+    /// Buffer not entirely mapped.
+    BadAddress = 3,
+    /// Invalid argument value.
+    InvalidArgument = 4,
+}
+
+impl SyscallError {
+    /// Value returned in `rax` for this error.
+    #[must_use]
+    pub const fn as_return_value(self) -> u64 {
+        0_u64.wrapping_sub(self as u64)
+    }
+
+    /// Decodes the value returned by a system call in `rax`.
+    ///
+    /// # Parameters
+    /// * `value` - The value of `rax` after the system call.
+    ///
+    /// # Errors
+    /// The error reported by the system call, if any.
+    pub const fn decode(value: u64) -> Result<u64, Self> {
+        match 0_u64.wrapping_sub(value) {
+            1 => Err(Self::UnknownSyscall),
+            2 => Err(Self::BadDescriptor),
+            3 => Err(Self::BadAddress),
+            4 => Err(Self::InvalidArgument),
+            _ => Ok(value),
+        }
+    }
+}
+
+impl fmt::Display for SyscallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownSyscall => write!(f, "unknown system call"),
+            Self::BadDescriptor => write!(f, "bad file descriptor"),
+            Self::BadAddress => write!(f, "bad address"),
+            Self::InvalidArgument => write!(f, "invalid argument"),
+        }
+    }
+}
+
+/// Implementation of a system call.
+type SyscallHandler = fn(&SyscallFrame, PrivilegeLevel) -> Result<u64, SyscallError>;
+
+/// System calls implementations, indexed by [`Syscall`] number.
+static SYSCALLS: [SyscallHandler; 4] = [sys_write, sys_exit, sys_yield, sys_sleep];
+
+/// Enables the `syscall` and `sysret` instructions.
+///
+/// System calls run on the stack of the calling thread, set up by
+/// [`thread::init`].
+pub fn init() {
+    let selectors = gdt::selectors();
+    Star::write(
+        selectors.user_code,
+        selectors.user_data,
+        selectors.code,
+        selectors.data,
+    )
+    .expect("invalid segments for syscall");
+    LStar::write(VirtAddr::from_ptr(entry::syscall_entry as *const ()));
+    // the stack switch is not reentrant, and the kernel expects a clear
+    // direction flag
+    SFMask::write(RFlags::INTERRUPT_FLAG | RFlags::TRAP_FLAG | RFlags::DIRECTION_FLAG);
+    // SAFETY:
+    // The entry point and segments are set up above.
+    unsafe {
+        Efer::update(|flags| flags.insert(EferFlags::SYSTEM_CALL_EXTENSIONS));
+    }
+}
+
+/// Handles a `syscall` instruction, which only user mode code executes.
+///
+/// # Parameters
+/// * `frame` - Registers of the caller.
+extern "C" fn syscall_handler(frame: &mut SyscallFrame) {
+    dispatch(frame, PrivilegeLevel::Ring3);
+}
+
+/// Dispatches a system call to its implementation.
+///
+/// The result is written back in `rax`.
+///
+/// # Parameters
+/// * `frame` - Registers of the caller,
+/// * `caller` - Privilege level of the caller.
+pub(crate) fn dispatch(frame: &mut SyscallFrame, caller: PrivilegeLevel) {
+    let result = usize::try_from(frame.number())
+        .ok()
+        .and_then(|number| SYSCALLS.get(number))
+        .map_or(Err(SyscallError::UnknownSyscall), |handler| {
+            handler(frame, caller)
+        });
+    frame.rax = match result {
+        Ok(value) => value,
+        Err(error) => error.as_return_value(),
+    };
+}
+
+/// Checks that a buffer of the caller is entirely mapped, and accessible to
+/// the caller.
+///
+/// # Parameters
+/// * `address` - Start of the buffer,
+/// * `length` - Length of the buffer,
+/// * `caller` - Privilege level of the caller.
+fn caller_buffer(
+    address: u64,
+    length: u64,
+    caller: PrivilegeLevel,
+) -> Result<&'static [u8], SyscallError> {
+    if length == 0 {
+        return Ok(&[]);
+    }
+    let end = address
+        .checked_add(length - 1)
+        .ok_or(SyscallError::BadAddress)?;
+    let start = VirtAddr::try_new(address).map_err(|_error| SyscallError::BadAddress)?;
+    let end = VirtAddr::try_new(end).map_err(|_error| SyscallError::BadAddress)?;
+    let pages = Page::<Size4KiB>::range_inclusive(
+        Page::containing_address(start),
+        Page::containing_address(end),
+    );
+    let accessible = |page: Page| match caller {
+        PrivilegeLevel::Ring0 => translate_addr(page.start_address()).is_some(),
+        _ => is_user_accessible(page.start_address()),
+    };
+    if !pages.into_iter().all(accessible) {
+        return Err(SyscallError::BadAddress);
+    }
+    let length = usize::try_from(length).map_err(|_error| SyscallError::InvalidArgument)?;
+    // SAFETY:
+    // The whole buffer is mapped.
+    Ok(unsafe { slice::from_raw_parts(start.as_ptr(), length) })
+}
+
+fn sys_write(frame: &SyscallFrame, caller: PrivilegeLevel) -> Result<u64, SyscallError> {
+    let [descriptor, address, length, ..] = frame.arguments();
+    let buffer = caller_buffer(address, length, caller)?;
+    let text = str::from_utf8(buffer).map_err(|_error| SyscallError::InvalidArgument)?;
+    match descriptor {
+        1 => print!("{text}"),
+        2 => {
+            serial_print!("{text}");
+        }
+        _ => return Err(SyscallError::BadDescriptor),
+    }
+    Ok(length)
+}
+
+fn sys_exit(frame: &SyscallFrame, _caller: PrivilegeLevel) -> Result<u64, SyscallError> {
+    let [status, ..] = frame.arguments();
+    user::exit(status);
+    // not called from a user program: nothing to return to
+    println!("exit called with status {status}");
+    hlt_loop();
+}
+
+#[expect(
+    clippy::unnecessary_wraps,
+    reason = "signature of the system calls table"
+)]
+fn sys_yield(_frame: &SyscallFrame, _caller: PrivilegeLevel) -> Result<u64, SyscallError> {
+    thread::yield_now();
+    Ok(0)
+}
+
+#[expect(
+    clippy::unnecessary_wraps,
+    reason = "signature of the system calls table"
+)]
+fn sys_sleep(frame: &SyscallFrame, _caller: PrivilegeLevel) -> Result<u64, SyscallError> {
+    let [milliseconds, ..] = frame.arguments();
+    thread::sleep(Duration::from_millis(milliseconds));
+    Ok(0)
+}
+
+/// Builds a frame calling the given system call.
+#[cfg(test)]
+const fn caller_frame(syscall: u64, arguments: [u64; 3]) -> SyscallFrame {
+    let [rdi, rsi, rdx] = arguments;
+    SyscallFrame {
+        r15: 0,
+        r14: 0,
+        r13: 0,
+        r12: 0,
+        r10: 0,
+        r9: 0,
+        r8: 0,
+        rbp: 0,
+        rdi,
+        rsi,
+        rdx,
+        rbx: 0,
+        rax: syscall,
+        rflags: 0,
+        rip: 0,
+        rsp: 0,
+    }
+}
+
+/// Unknown system calls are rejected.
+#[test_case]
+fn unknown_syscall() {
+    let mut frame = caller_frame(42, [0; 3]);
+    dispatch(&mut frame, PrivilegeLevel::Ring0);
+    assert_eq!(
+        SyscallError::decode(frame.rax),
+        Err(SyscallError::UnknownSyscall),
+        "unknown system call accepted"
+    );
+}
+
+/// Writing returns the number of bytes written, and checks its buffer.
+#[test_case]
+fn write() {
+    let text = "syscall write test\n";
+    let mut written = caller_frame(
+        Syscall::Write as u64,
+        [2, text.as_ptr() as u64, text.len() as u64],
+    );
+    dispatch(&mut written, PrivilegeLevel::Ring0);
+    assert_eq!(
+        SyscallError::decode(written.rax),
+        Ok(text.len() as u64),
+        "write failed"
+    );
+
+    let mut unmapped = caller_frame(Syscall::Write as u64, [2, 0x7FFF_FFFF_0000, 16]);
+    dispatch(&mut unmapped, PrivilegeLevel::Ring0);
+    assert_eq!(
+        SyscallError::decode(unmapped.rax),
+        Err(SyscallError::BadAddress),
+        "unmapped buffer accepted"
+    );
+
+    let mut invalid = caller_frame(Syscall::Write as u64, [7, text.as_ptr() as u64, 1]);
+    dispatch(&mut invalid, PrivilegeLevel::Ring0);
+    assert_eq!(
+        SyscallError::decode(invalid.rax),
+        Err(SyscallError::BadDescriptor),
+        "invalid descriptor accepted"
+    );
+}
+
+/// Sleeping waits for the timer.
+#[test_case]
+fn sleep() {
+    use crate::tasks::timer::ticks;
+
+    let start = ticks();
+    let mut frame = caller_frame(Syscall::Sleep as u64, [20, 0, 0]);
+    dispatch(&mut frame, PrivilegeLevel::Ring0);
+    assert_eq!(SyscallError::decode(frame.rax), Ok(0), "sleep failed");
+    assert!(ticks() > start, "sleep returned immediately");
+}
@@ -26,21 +26,48 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use super::{Task, TaskId};
-use alloc::{collections::BTreeMap, sync::Arc, task::Wake};
-use core::task::{Context, Poll, Waker};
-use crossbeam_queue::ArrayQueue;
+use super::{
+    joinable,
+    metrics::{self, TaskMetrics},
+    JoinHandle, Priority, Task, TaskId,
+};
+use alloc::{boxed::Box, collections::BTreeMap, sync::Arc, task::Wake};
+use core::{
+    array,
+    future::Future,
+    iter,
+    pin::pin,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+use crossbeam_queue::SegQueue;
 use x86_64::instructions::{
     hlt,
     interrupts::{self, enable_and_hlt},
 };
 
-const MAX_TASKS: usize = 100;
+/// Tasks polled from each priority level per scheduling pass, most urgent
+/// first: every level gets a share of the processor.
+const WEIGHTS: [usize; Priority::COUNT] = [8, 4, 1];
+/// Tasks polled before the executor checks for new tasks.
+const POLL_BUDGET: usize = 32;
+
+/// Body of a task spawned through a [`Spawner`], with its priority and name.
+type Injected = (
+    Priority,
+    Option<&'static str>,
+    Pin<Box<dyn Future<Output = ()> + Send>>,
+);
 
 pub struct Executor {
     tasks: BTreeMap<TaskId, Task>,
-    task_queue: Arc<ArrayQueue<TaskId>>,
+    /// Ready tasks; each one is queued at most once, thanks to the `scheduled`
+    /// flag of its metrics.
+    task_queue: Arc<ReadyQueues>,
     waker_cache: BTreeMap<TaskId, Waker>,
+    /// Tasks spawned through the spawners, not started yet.
+    injected: Arc<SegQueue<Injected>>,
 }
 
 impl Executor {
@@ -48,51 +75,196 @@ impl Executor {
     pub fn new() -> Self {
         Self {
             tasks: BTreeMap::new(),
-            task_queue: Arc::new(ArrayQueue::new(MAX_TASKS)),
+            task_queue: Arc::new(ReadyQueues::new()),
             waker_cache: BTreeMap::new(),
+            injected: Arc::new(SegQueue::new()),
         }
     }
 
-    pub fn spawn(&mut self, task: Task) {
-        let task_id = task.id;
+    /// Handle spawning tasks on this executor, from anywhere.
+    #[must_use]
+    pub fn spawner(&self) -> Spawner {
+        Spawner {
+            injected: Arc::clone(&self.injected),
+        }
+    }
+
+    /// Spawns a task running the given future.
+    ///
+    /// Returns a handle resolving to the output of the future; dropping it
+    /// detaches the task.
+    ///
+    /// # Parameters
+    /// * `future` - The future run by the task.
+    pub fn spawn<F>(&mut self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        self.spawn_with_priority(Priority::default(), future)
+    }
+
+    /// Spawns a task running the given future, with the given priority.
+    ///
+    /// # Parameters
+    /// * `priority` - The priority of the task,
+    /// * `future` - The future run by the task.
+    pub fn spawn_with_priority<F>(&mut self, priority: Priority, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let (task, handle) = Task::with_handle(future);
+        self.spawn_task(task.with_priority(priority));
+        handle
+    }
+
+    /// Spawns a named task running the given future.
+    ///
+    /// The name shows up in the [task statistics](metrics::task_stats).
+    ///
+    /// # Parameters
+    /// * `name` - The name of the task,
+    /// * `future` - The future run by the task.
+    pub fn spawn_named<F>(&mut self, name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let (task, handle) = Task::with_handle(future);
+        self.spawn_task(task.with_name(name));
+        handle
+    }
+
+    /// Spawns a task.
+    ///
+    /// # Parameters
+    /// * `task` - The task to run.
+    pub fn spawn_task(&mut self, task: Task) {
+        let (task_id, priority) = (task.id, task.priority);
+        task.metrics.scheduled.store(true, Ordering::Release);
+        metrics::register(task_id, task.name, priority, &task.metrics);
         assert!(
             self.tasks.insert(task.id, task).is_none(),
             "task with same ID already in tasks"
         );
-        self.task_queue.push(task_id).expect("queue full");
+        self.task_queue.push(priority, task_id);
+    }
+
+    /// Starts the tasks spawned through the spawners.
+    fn spawn_injected(&mut self) {
+        while let Some((priority, name, future)) = self.injected.pop() {
+            let mut task = Task::from_boxed(future).with_priority(priority);
+            task.name = name;
+            self.spawn_task(task);
+        }
     }
 
+    /// Polls the ready tasks, within the poll budget.
     fn run_ready_tasks(&mut self) {
-        while let Some(task_id) = self.task_queue.pop() {
-            let Some(task) = self.tasks.get_mut(&task_id) else {
-                continue;
-            };
-            let waker = self
-                .waker_cache
-                .entry(task_id)
-                .or_insert_with(|| TaskWaker::new(task_id, Arc::clone(&self.task_queue)));
-            let mut context = Context::from_waker(waker);
-            match task.poll(&mut context) {
-                Poll::Ready(()) => {
-                    // task done -> remove it and its cached waker
-                    self.tasks.remove(&task_id);
-                    self.waker_cache.remove(&task_id);
-                }
-                Poll::Pending => {}
+        let task_queue = Arc::clone(&self.task_queue);
+        task_queue.pop_batch(POLL_BUDGET, |task_id| self.poll_task(task_id));
+    }
+
+    /// Polls a ready task.
+    fn poll_task(&mut self, task_id: TaskId) {
+        let Some(task) = self.tasks.get_mut(&task_id) else {
+            return;
+        };
+        // wakeups from now on must poll the task again
+        task.metrics.scheduled.store(false, Ordering::Release);
+        let waker = self.waker_cache.entry(task_id).or_insert_with(|| {
+            TaskWaker::new(
+                task_id,
+                task.priority,
+                Arc::clone(&task.metrics),
+                Arc::clone(&self.task_queue),
+            )
+        });
+        let mut context = Context::from_waker(waker);
+        let Task {
+            metrics: counters,
+            future,
+            locals,
+            ..
+        } = task;
+        match locals.scope(|| counters.record_poll(|| future.as_mut().poll(&mut context))) {
+            Poll::Ready(()) => {
+                // task done (or aborted) -> remove it and its cached waker
+                self.tasks.remove(&task_id);
+                self.waker_cache.remove(&task_id);
+                metrics::unregister(task_id);
             }
+            Poll::Pending => {}
         }
     }
 
     pub fn run(&mut self) -> ! {
         loop {
+            self.spawn_injected();
+            self.run_ready_tasks();
+            self.sleep_if_idle(None);
+        }
+    }
+
+    /// Runs the tasks until the given future completes, returning its output.
+    ///
+    /// The future is polled on the current stack, so it may borrow local
+    /// data. Tasks spawned on the executor keep running while it waits, and
+    /// the CPU halts when nothing is ready. Interrupts are enabled.
+    ///
+    /// # Parameters
+    /// * `future` - The future to complete.
+    pub fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let main = Arc::new(MainWaker {
+            woken: AtomicBool::new(true),
+        });
+        let waker = Waker::from(Arc::clone(&main));
+        let mut context = Context::from_waker(&waker);
+        loop {
+            if main.woken.swap(false, Ordering::AcqRel) {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                    return output;
+                }
+            }
+            self.spawn_injected();
             self.run_ready_tasks();
-            self.sleep_if_idle();
+            self.sleep_if_idle(Some(&main.woken));
         }
     }
 
-    fn sleep_if_idle(&self) {
+    /// Runs the tasks until none is left.
+    ///
+    /// The CPU halts while every task waits for an event, such as a timer or
+    /// keyboard input. Interrupts are enabled.
+    pub fn run_until_idle(&mut self) {
+        loop {
+            self.spawn_injected();
+            if self.tasks.is_empty() && self.injected.is_empty() {
+                return;
+            }
+            self.run_ready_tasks();
+            self.sleep_if_idle(None);
+        }
+    }
+
+    /// Whether no task is ready or waiting to be started.
+    fn is_idle(&self) -> bool {
+        self.task_queue.is_empty() && self.injected.is_empty()
+    }
+
+    /// Halts the CPU until the next interrupt if no task is ready.
+    ///
+    /// Keyboard input and expired timers both wake tasks from their
+    /// interrupt handlers, which ends the `hlt`.
+    ///
+    /// # Parameters
+    /// * `main_woken` - Wake-up flag of the future [`Self::block_on`] waits
+    ///   for, which also prevents halting.
+    fn sleep_if_idle(&self, main_woken: Option<&AtomicBool>) {
         interrupts::disable();
-        if self.task_queue.is_empty() {
+        if self.is_idle() && main_woken.is_none_or(|woken| !woken.load(Ordering::Acquire)) {
             enable_and_hlt();
         } else {
             interrupts::enable();
@@ -100,28 +272,175 @@ impl Executor {
     }
 }
 
+impl Drop for Executor {
+    fn drop(&mut self) {
+        for &task_id in self.tasks.keys() {
+            metrics::unregister(task_id);
+        }
+    }
+}
+
 impl Default for Executor {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Cloneable handle spawning tasks on an [`Executor`].
+///
+/// Unlike [`Executor::spawn`], it can be used by running tasks and by
+/// interrupt handlers: the tasks are queued, and started by the executor on
+/// its next loop.
+#[derive(Clone)]
+pub struct Spawner {
+    injected: Arc<SegQueue<Injected>>,
+}
+
+impl Spawner {
+    /// Spawns a task running the given future.
+    ///
+    /// Returns a handle resolving to the output of the future; dropping it
+    /// detaches the task.
+    ///
+    /// # Parameters
+    /// * `future` - The future run by the task.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_with_priority(Priority::default(), future)
+    }
+
+    /// Spawns a task running the given future, with the given priority.
+    ///
+    /// # Parameters
+    /// * `priority` - The priority of the task,
+    /// * `future` - The future run by the task.
+    pub fn spawn_with_priority<F>(&self, priority: Priority, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.inject(priority, None, future)
+    }
+
+    /// Spawns a named task running the given future.
+    ///
+    /// The name shows up in the [task statistics](metrics::task_stats).
+    ///
+    /// # Parameters
+    /// * `name` - The name of the task,
+    /// * `future` - The future run by the task.
+    pub fn spawn_named<F>(&self, name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.inject(Priority::default(), Some(name), future)
+    }
+
+    /// Queues a task for the executor to start.
+    fn inject<F>(
+        &self,
+        priority: Priority,
+        name: Option<&'static str>,
+        future: F,
+    ) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (body, handle) = joinable(future);
+        self.injected.push((priority, name, Box::pin(body)));
+        handle
+    }
+}
+
+/// Ready tasks, with one queue per priority level.
+struct ReadyQueues {
+    queues: [SegQueue<TaskId>; Priority::COUNT],
+}
+
+impl ReadyQueues {
+    fn new() -> Self {
+        Self {
+            queues: array::from_fn(|_| SegQueue::new()),
+        }
+    }
+
+    fn push(&self, priority: Priority, task_id: TaskId) {
+        self.queues[priority as usize].push(task_id);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queues.iter().all(SegQueue::is_empty)
+    }
+
+    /// Pops ready tasks in weighted round robin, most urgent level first, so
+    /// that no level starves.
+    ///
+    /// # Parameters
+    /// * `budget` - Maximum number of tasks to pop,
+    /// * `poll` - Called on every task popped.
+    fn pop_batch(&self, mut budget: usize, mut poll: impl FnMut(TaskId)) {
+        while budget > 0 && !self.is_empty() {
+            for (queue, weight) in self.queues.iter().zip(WEIGHTS) {
+                for task_id in iter::from_fn(|| queue.pop()).take(weight.min(budget)) {
+                    budget -= 1;
+                    poll(task_id);
+                }
+            }
+        }
+    }
+}
+
+/// Waker of the future passed to [`Executor::block_on`].
+struct MainWaker {
+    /// Whether the future must be polled again.
+    woken: AtomicBool,
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+    }
+}
+
 struct TaskWaker {
     task_id: TaskId,
-    task_queue: Arc<ArrayQueue<TaskId>>,
+    priority: Priority,
+    /// Counters of the task, and whether it is already queued.
+    metrics: Arc<TaskMetrics>,
+    task_queue: Arc<ReadyQueues>,
 }
 
 impl TaskWaker {
     #[expect(clippy::new_ret_no_self)]
-    fn new(task_id: TaskId, task_queue: Arc<ArrayQueue<TaskId>>) -> Waker {
+    fn new(
+        task_id: TaskId,
+        priority: Priority,
+        metrics: Arc<TaskMetrics>,
+        task_queue: Arc<ReadyQueues>,
+    ) -> Waker {
         Waker::from(Arc::new(Self {
             task_id,
+            priority,
+            metrics,
             task_queue,
         }))
     }
 
     fn wake_task(&self) {
-        self.task_queue.push(self.task_id).expect("task_queue full");
+        self.metrics.record_wake();
+        // a task woken several times before being polled is queued once
+        if !self.metrics.scheduled.swap(true, Ordering::AcqRel) {
+            self.task_queue.push(self.priority, self.task_id);
+        }
     }
 }
 
@@ -134,3 +453,84 @@ impl Wake for TaskWaker {
         self.wake_task();
     }
 }
+
+/// A task woken many times before being polled is queued once.
+#[test_case]
+fn wakeups_are_deduplicated() {
+    let task_queue = Arc::new(ReadyQueues::new());
+    let metrics = Arc::new(TaskMetrics::new());
+    let waker = TaskWaker::new(
+        TaskId::new(),
+        Priority::default(),
+        Arc::clone(&metrics),
+        Arc::clone(&task_queue),
+    );
+    let queued = || task_queue.queues.iter().map(SegQueue::len).sum::<usize>();
+    for _ in 0..1000 {
+        waker.wake_by_ref();
+    }
+    assert_eq!(queued(), 1, "task queued several times");
+    metrics.scheduled.store(false, Ordering::Release);
+    waker.wake();
+    assert_eq!(queued(), 2, "polled task not queued again");
+}
+
+/// Urgent tasks are served first, without starving the background ones.
+#[test_case]
+fn background_tasks_are_not_starved() {
+    use alloc::vec::Vec;
+
+    let task_queue = ReadyQueues::new();
+    let mut background = Vec::new();
+    for _ in 0..POLL_BUDGET {
+        task_queue.push(Priority::Deferred, TaskId::new());
+        let task_id = TaskId::new();
+        background.push(task_id);
+        task_queue.push(Priority::Background, task_id);
+    }
+    let mut polled = Vec::new();
+    task_queue.pop_batch(WEIGHTS[0] + 1, |task_id| polled.push(task_id));
+    assert_eq!(polled.len(), WEIGHTS[0] + 1, "budget not used");
+    assert!(
+        polled[..WEIGHTS[0]]
+            .iter()
+            .all(|task| !background.contains(task)),
+        "background task served first"
+    );
+    assert!(
+        background.contains(&polled[WEIGHTS[0]]),
+        "background task starved"
+    );
+}
+
+/// `block_on` returns the output of its future while spawned tasks run.
+#[test_case]
+fn block_on_runs_tasks() {
+    use super::timer;
+    use core::time::Duration;
+
+    let mut executor = Executor::new();
+    let task = executor.spawn(timer::sleep(Duration::from_millis(5)));
+    let borrowed = 6;
+    let output = executor.block_on(async {
+        assert_eq!(task.await, Ok(()), "spawned task not run");
+        borrowed * 7
+    });
+    assert_eq!(output, 42, "wrong output");
+}
+
+/// `run_until_idle` returns once every task finished, waiting ones included.
+#[test_case]
+fn run_until_idle_finishes_tasks() {
+    use super::timer;
+    use core::time::Duration;
+
+    let mut executor = Executor::new();
+    let ready = executor.spawn(async { 42 });
+    let waiting = executor.spawn(timer::sleep(Duration::from_millis(5)));
+    executor.spawner().spawn(async {});
+    executor.run_until_idle();
+    assert!(ready.is_finished(), "ready task not run");
+    assert!(waiting.is_finished(), "waiting task left");
+    assert!(executor.tasks.is_empty(), "tasks left");
+}
@@ -0,0 +1,202 @@
+// File: src/tasks/join.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::sync::Arc;
+use core::{
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+};
+
+use futures_util::task::AtomicWaker;
+
+use crate::spinlock::IrqSpinlock;
+
+/// Output of a task, as seen by its [`JoinHandle`].
+enum Slot<T> {
+    /// The task is still running.
+    Running,
+    /// The task finished with this output.
+    Finished(T),
+    /// The task was aborted before finishing.
+    Cancelled,
+    /// The output was handed to the joiner.
+    Taken,
+}
+
+/// Error returned by a [`JoinHandle`] when its task was aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("task cancelled")
+    }
+}
+
+/// Abort request of a task, shared with its handles.
+pub(super) struct AbortFlag {
+    /// Whether the task must stop.
+    aborted: AtomicBool,
+    /// Waker of the task, to get it scheduled once aborted.
+    task: AtomicWaker,
+}
+
+impl AbortFlag {
+    /// Flag of a task that was not aborted.
+    pub(super) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            aborted: AtomicBool::new(false),
+            task: AtomicWaker::new(),
+        })
+    }
+
+    /// Whether the task was aborted, registering its waker otherwise.
+    ///
+    /// Called by the task at every poll.
+    ///
+    /// # Parameters
+    /// * `cx` - Context of the task.
+    pub(super) fn poll_aborted(&self, cx: &Context<'_>) -> bool {
+        self.task.register(cx.waker());
+        self.aborted.load(Ordering::Acquire)
+    }
+
+    /// Requests the task to stop, and wakes it.
+    fn abort(&self) {
+        self.aborted.store(true, Ordering::Release);
+        self.task.wake();
+    }
+}
+
+/// State shared by a task and its [`JoinHandle`].
+pub(super) struct JoinState<T> {
+    /// Output of the task.
+    slot: IrqSpinlock<Slot<T>>,
+    /// Waker of the task joining.
+    waker: AtomicWaker,
+}
+
+impl<T> JoinState<T> {
+    /// State of a running task.
+    pub(super) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            slot: IrqSpinlock::new(Slot::Running),
+            waker: AtomicWaker::new(),
+        })
+    }
+
+    /// Stores the output of the task, and wakes the joiner.
+    ///
+    /// # Parameters
+    /// * `output` - The output of the task.
+    pub(super) fn finish(&self, output: T) {
+        *self.slot.lock() = Slot::Finished(output);
+        self.waker.wake();
+    }
+
+    /// Records that the task was aborted, and wakes the joiner.
+    pub(super) fn cancel(&self) {
+        *self.slot.lock() = Slot::Cancelled;
+        self.waker.wake();
+    }
+}
+
+/// Handle to a spawned task: a future resolving to the task's output, or to
+/// [`Cancelled`] if the task was aborted.
+///
+/// Dropping the handle detaches the task, which keeps running.
+pub struct JoinHandle<T> {
+    state: Arc<JoinState<T>>,
+    abort: Arc<AbortFlag>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Handle on the given states.
+    pub(super) const fn new(state: Arc<JoinState<T>>, abort: Arc<AbortFlag>) -> Self {
+        Self { state, abort }
+    }
+
+    /// Whether the task finished, or was aborted.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        !matches!(*self.state.slot.lock(), Slot::Running)
+    }
+
+    /// Aborts the task.
+    ///
+    /// Its future is dropped the next time the executor gets to it, and the
+    /// handle then resolves to [`Cancelled`]. Aborting a finished task does
+    /// nothing.
+    pub fn abort(&self) {
+        self.abort.abort();
+    }
+
+    /// Handle aborting the task, without access to its output.
+    #[must_use]
+    pub fn abort_handle(&self) -> AbortHandle {
+        AbortHandle {
+            abort: Arc::clone(&self.abort),
+        }
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, Cancelled>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.state.slot.lock();
+        match mem::replace(&mut *slot, Slot::Taken) {
+            Slot::Finished(output) => Poll::Ready(Ok(output)),
+            Slot::Cancelled => Poll::Ready(Err(Cancelled)),
+            Slot::Running => {
+                *slot = Slot::Running;
+                // registered under the lock: the task cannot finish in between
+                self.state.waker.register(cx.waker());
+                Poll::Pending
+            }
+            Slot::Taken => panic!("JoinHandle polled after completion"),
+        }
+    }
+}
+
+/// Handle aborting a spawned task, see [`JoinHandle::abort`].
+#[derive(Clone)]
+pub struct AbortHandle {
+    abort: Arc<AbortFlag>,
+}
+
+impl AbortHandle {
+    /// Aborts the task.
+    pub fn abort(&self) {
+        self.abort.abort();
+    }
+}
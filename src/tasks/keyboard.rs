@@ -111,3 +111,13 @@ pub async fn print_keypresses() {
         }
     }
 }
+
+crate::async_test! {
+    timeout = Duration::from_secs(1);
+    /// Scancodes from the interrupt handler come out of the stream.
+    async fn scancodes_are_streamed() {
+        let mut scancodes = ScancodeStream::new();
+        add_scancode(0x1E);
+        assert_eq!(scancodes.next().await, Some(0x1E), "scancode lost");
+    }
+}
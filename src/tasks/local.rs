@@ -0,0 +1,318 @@
+// File: src/tasks/local.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::{collections::BTreeMap, rc::Rc};
+use core::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    marker::PhantomData,
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+/// Locals of the task polled by the running thread, null outside of a poll.
+///
+/// Any thread may run an executor: the scheduler saves and restores the
+/// pointer on every thread switch, with [`switch_current`].
+static CURRENT: AtomicPtr<TaskLocals> = AtomicPtr::new(ptr::null_mut());
+
+/// Locals of the task polled by a thread that is switched out.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SavedLocals(*mut TaskLocals);
+
+// SAFETY:
+// The pointer is only used again by the thread that saved it.
+unsafe impl Send for SavedLocals {}
+
+impl SavedLocals {
+    /// A thread that is not polling a task.
+    pub(crate) const NONE: Self = Self(ptr::null_mut());
+}
+
+/// Makes the locals of the thread about to run current.
+///
+/// Called by the scheduler, with interrupts disabled. Returns the locals of
+/// the thread switched from.
+///
+/// # Parameters
+/// * `next` - The locals saved by the thread about to run.
+pub(crate) fn switch_current(next: SavedLocals) -> SavedLocals {
+    SavedLocals(CURRENT.swap(next.0, Ordering::AcqRel))
+}
+
+/// Declares task-local keys, each task holding its own value for them.
+///
+/// Keys are declared like statics without initializer, such as
+/// `pub static USER_ID: u32;`. Values are given at spawn time with
+/// [`Task::with_local`](super::Task::with_local), or set by the task itself.
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::tasks::local::LocalKey<$ty> =
+            $crate::tasks::local::LocalKey::new(stringify!($name));
+        $crate::task_local!($($rest)*);
+    };
+}
+
+/// Values of the task-local keys set in a task, indexed by key address.
+#[derive(Default)]
+pub(super) struct TaskLocals {
+    values: RefCell<BTreeMap<usize, Rc<dyn Any>>>,
+}
+
+impl TaskLocals {
+    /// Sets the value of a key.
+    ///
+    /// # Parameters
+    /// * `key` - The key,
+    /// * `value` - Its new value.
+    pub(super) fn insert<T: 'static>(&self, key: &'static LocalKey<T>, value: T) {
+        self.values
+            .borrow_mut()
+            .insert(key.address(), Rc::new(value));
+    }
+
+    /// Makes these locals current while polling a task.
+    ///
+    /// The previous locals are restored afterwards, so that a task can be
+    /// polled from within another one.
+    ///
+    /// # Parameters
+    /// * `poll` - Polls the task.
+    pub(super) fn scope<R>(&self, poll: impl FnOnce() -> R) -> R {
+        let previous = CURRENT.swap(ptr::from_ref(self).cast_mut(), Ordering::AcqRel);
+        let result = poll();
+        CURRENT.store(previous, Ordering::Release);
+        result
+    }
+}
+
+/// Error returned when accessing a task-local key outside of a task, or in
+/// a task without value for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError {
+    /// Name of the key.
+    pub key: &'static str,
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "task-local {} is not set", self.key)
+    }
+}
+
+/// A task-local key, declared with [`task_local!`](crate::task_local).
+pub struct LocalKey<T: 'static> {
+    name: &'static str,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> LocalKey<T> {
+    /// Creates a key; use [`task_local!`](crate::task_local) instead.
+    ///
+    /// # Parameters
+    /// * `name` - Name of the key, for error messages.
+    #[doc(hidden)]
+    #[must_use]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _value: PhantomData,
+        }
+    }
+
+    /// Identifies the key in the task locals: keys are statics, each with its
+    /// own address.
+    fn address(&'static self) -> usize {
+        ptr::from_ref(self).addr()
+    }
+
+    /// Runs a function on the locals of the task being polled.
+    ///
+    /// # Parameters
+    /// * `function` - Called with the locals, which it cannot keep.
+    fn with_current<R, F>(&'static self, function: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&TaskLocals) -> R,
+    {
+        let locals = CURRENT.load(Ordering::Acquire);
+        // SAFETY:
+        // The pointer is set by `TaskLocals::scope` for the thread polling a
+        // task, and follows that thread across switches: it points to the
+        // locals of the task this code runs in, which outlive the call.
+        unsafe { locals.as_ref() }
+            .map(function)
+            .ok_or(AccessError { key: self.name })
+    }
+
+    /// Sets the value of the key for the current task.
+    ///
+    /// # Parameters
+    /// * `value` - The new value.
+    ///
+    /// # Errors
+    /// Fails outside of a task.
+    pub fn try_set(&'static self, value: T) -> Result<(), AccessError> {
+        self.with_current(|locals| locals.insert(self, value))
+    }
+
+    /// Sets the value of the key for the current task.
+    ///
+    /// # Parameters
+    /// * `value` - The new value.
+    ///
+    /// # Panics
+    /// Panics outside of a task.
+    pub fn set(&'static self, value: T) {
+        if let Err(error) = self.try_set(value) {
+            panic!("{error}");
+        }
+    }
+
+    /// Runs a function on the value of the key for the current task.
+    ///
+    /// # Parameters
+    /// * `function` - Called with the value.
+    ///
+    /// # Errors
+    /// Fails outside of a task, or if the task has no value for the key.
+    pub fn try_with<R, F>(&'static self, function: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let error = AccessError { key: self.name };
+        // the map is not borrowed while the function runs, so that it can
+        // set other keys
+        let value = self
+            .with_current(|locals| locals.values.borrow().get(&self.address()).cloned())?
+            .ok_or(error)?;
+        value.downcast_ref().map(function).ok_or(error)
+    }
+
+    /// Runs a function on the value of the key for the current task.
+    ///
+    /// # Parameters
+    /// * `function` - Called with the value.
+    ///
+    /// # Panics
+    /// Panics outside of a task, or if the task has no value for the key.
+    pub fn with<R, F>(&'static self, function: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.try_with(function)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Copy of the value of the key for the current task.
+    ///
+    /// # Panics
+    /// Panics outside of a task, or if the task has no value for the key.
+    #[must_use]
+    pub fn get(&'static self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+}
+
+impl<T: 'static> fmt::Debug for LocalKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKey")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Each task sees its own values, and nothing outside of tasks.
+#[test_case]
+fn values_are_per_task() {
+    use super::{simple_executor::SimpleExecutor, timer, Task};
+    use core::time::Duration;
+
+    crate::task_local! {
+        static TEST_VALUE: u32;
+    }
+
+    assert!(
+        TEST_VALUE.try_with(|_| ()).is_err(),
+        "value outside of a task"
+    );
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(
+        Task::new(async {
+            assert_eq!(TEST_VALUE.get(), 7, "value set at spawn time lost");
+        })
+        .with_local(&TEST_VALUE, 7),
+    );
+    executor.spawn(Task::new(async {
+        assert!(
+            TEST_VALUE.try_with(|_| ()).is_err(),
+            "value of another task"
+        );
+        TEST_VALUE.set(3);
+        timer::sleep(Duration::from_millis(1)).await;
+        assert_eq!(TEST_VALUE.get(), 3, "value set by the task lost");
+    }));
+    executor.run();
+    assert!(
+        TEST_VALUE.try_with(|_| ()).is_err(),
+        "value left after the poll"
+    );
+}
+
+/// Threads polling tasks see the values of their own task across switches.
+#[test_case]
+fn values_follow_threads() {
+    use super::{simple_executor::SimpleExecutor, Task};
+    use crate::thread;
+
+    crate::task_local! {
+        static THREAD_VALUE: u32;
+    }
+    let poll_with = |value| {
+        move || {
+            let mut executor = SimpleExecutor::new();
+            let task = Task::new(async move {
+                for _ in 0..10 {
+                    thread::yield_now();
+                    assert_eq!(THREAD_VALUE.get(), value, "value of another thread");
+                }
+            });
+            executor.spawn(task.with_local(&THREAD_VALUE, value));
+            executor.run();
+        }
+    };
+    let other = thread::spawn_thread(poll_with(2));
+    poll_with(1)();
+    let () = other.join();
+}
@@ -0,0 +1,237 @@
+// File: src/tasks/metrics.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::{
+    arch::x86_64::_rdtsc,
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+use super::{Priority, TaskId};
+use crate::spinlock::IrqSpinlock;
+
+/// Tasks spawned on an executor and not finished yet.
+static LIVE_TASKS: IrqSpinlock<BTreeMap<TaskId, LiveTask>> = IrqSpinlock::new(BTreeMap::new());
+
+/// A task listed in [`LIVE_TASKS`].
+struct LiveTask {
+    name: Option<&'static str>,
+    priority: Priority,
+    metrics: Arc<TaskMetrics>,
+}
+
+/// Counters of a task, updated by its executor and its waker.
+pub(super) struct TaskMetrics {
+    /// Whether the task is in the ready queue of the executor.
+    pub(super) scheduled: AtomicBool,
+    polls: AtomicU64,
+    /// Time spent polling the task, in TSC cycles.
+    poll_cycles: AtomicU64,
+    wakes: AtomicU64,
+}
+
+impl TaskMetrics {
+    pub(super) const fn new() -> Self {
+        Self {
+            scheduled: AtomicBool::new(false),
+            polls: AtomicU64::new(0),
+            poll_cycles: AtomicU64::new(0),
+            wakes: AtomicU64::new(0),
+        }
+    }
+
+    /// Polls the task, recording how long it took.
+    ///
+    /// # Parameters
+    /// * `poll` - Polls the task.
+    pub(super) fn record_poll<T>(&self, poll: impl FnOnce() -> T) -> T {
+        // SAFETY:
+        // Reading the time stamp counter has no side effect.
+        let start = unsafe { _rdtsc() };
+        let result = poll();
+        // SAFETY:
+        // Reading the time stamp counter has no side effect.
+        let cycles = unsafe { _rdtsc() }.wrapping_sub(start);
+        self.polls.fetch_add(1, Ordering::Relaxed);
+        self.poll_cycles.fetch_add(cycles, Ordering::Relaxed);
+        result
+    }
+
+    /// Records that the task was woken.
+    ///
+    /// Called by wakers, possibly from interrupt handlers.
+    pub(super) fn record_wake(&self) {
+        self.wakes.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Lists a task spawned on an executor.
+///
+/// # Parameters
+/// * `id` - Identifier of the task,
+/// * `name` - Its name, if any,
+/// * `priority` - Its priority,
+/// * `metrics` - Its counters.
+pub(super) fn register(
+    id: TaskId,
+    name: Option<&'static str>,
+    priority: Priority,
+    metrics: &Arc<TaskMetrics>,
+) {
+    let task = LiveTask {
+        name,
+        priority,
+        metrics: Arc::clone(metrics),
+    };
+    LIVE_TASKS.lock().insert(id, task);
+}
+
+/// Removes a finished or dropped task from the list.
+///
+/// # Parameters
+/// * `id` - Identifier of the task.
+pub(super) fn unregister(id: TaskId) {
+    LIVE_TASKS.lock().remove(&id);
+}
+
+/// Scheduling state of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    /// Woken, waiting in the ready queue to be polled.
+    Ready,
+    /// Waiting for a waker to be called.
+    Waiting,
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Self::Ready => "ready",
+            Self::Waiting => "waiting",
+        })
+    }
+}
+
+/// Statistics of a single task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskStats {
+    /// Unique identifier of the task.
+    pub id: u64,
+    /// Name given when spawning the task.
+    pub name: Option<&'static str>,
+    /// Priority of the task.
+    pub priority: Priority,
+    /// Scheduling state of the task.
+    pub state: TaskState,
+    /// Number of times the task was polled.
+    pub polls: u64,
+    /// Time spent polling the task, in TSC cycles.
+    pub poll_cycles: u64,
+    /// Number of times the task was woken.
+    pub wakes: u64,
+}
+
+impl fmt::Display for TaskStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>5}: {:<8} {:>10} {:>14} {:>10}  {} ({:?})",
+            self.id,
+            self.state,
+            self.polls,
+            self.poll_cycles,
+            self.wakes,
+            self.name.unwrap_or("<unnamed>"),
+            self.priority
+        )
+    }
+}
+
+/// Statistics of every live task, ordered by identifier.
+///
+/// A task hogging the executor shows a large `poll_cycles`, a task stuck
+/// forever stays [`TaskState::Waiting`] with `wakes` not moving.
+#[must_use]
+pub fn task_stats() -> Vec<TaskStats> {
+    LIVE_TASKS
+        .lock()
+        .iter()
+        .map(|(id, task)| {
+            let metrics = &task.metrics;
+            TaskStats {
+                id: id.0,
+                name: task.name,
+                priority: task.priority,
+                state: if metrics.scheduled.load(Ordering::Acquire) {
+                    TaskState::Ready
+                } else {
+                    TaskState::Waiting
+                },
+                polls: metrics.polls.load(Ordering::Relaxed),
+                poll_cycles: metrics.poll_cycles.load(Ordering::Relaxed),
+                wakes: metrics.wakes.load(Ordering::Relaxed),
+            }
+        })
+        .collect()
+}
+
+/// Spawned tasks are listed until their executor drops them.
+#[test_case]
+fn spawned_tasks_are_listed() {
+    use super::executor::Executor;
+    use futures_util::future::pending;
+
+    let find = || {
+        task_stats()
+            .into_iter()
+            .find(|stats| stats.name == Some("metrics test"))
+    };
+    let mut executor = Executor::new();
+    drop(executor.spawn_named("metrics test", pending::<()>()));
+    let stats = find().expect("spawned task not listed");
+    assert_eq!(stats.state, TaskState::Ready, "spawned task not ready");
+    assert_eq!(stats.polls, 0, "task polled before running");
+    drop(executor);
+    assert!(find().is_none(), "dropped task still listed");
+}
+
+/// Polls and their duration are counted.
+#[test_case]
+fn polls_are_recorded() {
+    let metrics = TaskMetrics::new();
+    let value = metrics.record_poll(|| 42);
+    metrics.record_wake();
+    assert_eq!(value, 42, "wrong poll result");
+    assert_eq!(metrics.polls.load(Ordering::Relaxed), 1, "poll not counted");
+    assert!(
+        metrics.poll_cycles.load(Ordering::Relaxed) > 0,
+        "poll not timed"
+    );
+    assert_eq!(metrics.wakes.load(Ordering::Relaxed), 1, "wake not counted");
+}
@@ -26,17 +26,33 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use alloc::boxed::Box;
+use alloc::{boxed::Box, sync::Arc};
 use core::{
-    future::Future,
+    future::{poll_fn, Future},
     pin::Pin,
     sync::atomic::{AtomicU64, Ordering},
     task::{Context, Poll},
 };
 
 pub mod executor;
+/// Results of spawned tasks.
+mod join;
 pub mod keyboard;
+/// Task-local storage.
+pub mod local;
+/// Per-task statistics.
+pub mod metrics;
 pub mod simple_executor;
+/// Asynchronous synchronization primitives.
+pub mod sync;
+/// Timers and sleeping tasks.
+pub mod timer;
+
+use join::{AbortFlag, JoinState};
+pub use join::{AbortHandle, Cancelled, JoinHandle};
+use local::{LocalKey, TaskLocals};
+use metrics::TaskMetrics;
+pub use metrics::{task_stats, TaskState, TaskStats};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct TaskId(u64);
@@ -48,9 +64,34 @@ impl TaskId {
     }
 }
 
+/// Scheduling priority of a task.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Work deferred by interrupt handlers, served first.
+    Deferred,
+    /// Tasks the user waits on, such as input handling.
+    #[default]
+    Interactive,
+    /// Long running work, served with what is left.
+    Background,
+}
+
+impl Priority {
+    /// Number of priority levels.
+    const COUNT: usize = 3;
+}
+
 pub struct Task {
     id: TaskId,
+    /// Name shown in the task statistics.
+    name: Option<&'static str>,
+    /// Ready queue the task is put in.
+    priority: Priority,
     future: Pin<Box<dyn Future<Output = ()>>>,
+    /// Counters and scheduling state, shared with the waker of the task.
+    metrics: Arc<TaskMetrics>,
+    /// Values of the task-local keys.
+    locals: TaskLocals,
 }
 
 impl Task {
@@ -58,13 +99,140 @@ impl Task {
     where
         F: Future<Output = ()> + 'static,
     {
+        Self::from_boxed(Box::pin(future))
+    }
+
+    /// Creates a task reporting the output of the future to a handle.
+    ///
+    /// See [`JoinHandle::abort`] for aborting it.
+    ///
+    /// # Parameters
+    /// * `future` - The future run by the task.
+    pub fn with_handle<F>(future: F) -> (Self, JoinHandle<F::Output>)
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let (future, handle) = joinable(future);
+        (Self::new(future), handle)
+    }
+
+    /// Sets the priority of the task.
+    ///
+    /// # Parameters
+    /// * `priority` - The new priority.
+    #[must_use]
+    pub const fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Names the task, for the task statistics.
+    ///
+    /// # Parameters
+    /// * `name` - The name of the task.
+    #[must_use]
+    pub const fn with_name(mut self, name: &'static str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the value of a task-local key for the task.
+    ///
+    /// # Parameters
+    /// * `key` - The key, declared with [`task_local!`](crate::task_local),
+    /// * `value` - Its value.
+    #[must_use]
+    pub fn with_local<T: 'static>(self, key: &'static LocalKey<T>, value: T) -> Self {
+        self.locals.insert(key, value);
+        self
+    }
+
+    /// Creates a task from an already boxed future.
+    fn from_boxed(future: Pin<Box<dyn Future<Output = ()>>>) -> Self {
         Self {
             id: TaskId::new(),
-            future: Box::pin(future),
+            name: None,
+            priority: Priority::default(),
+            future,
+            metrics: Arc::new(TaskMetrics::new()),
+            locals: TaskLocals::default(),
         }
     }
 
     fn poll(&mut self, context: &mut Context) -> Poll<()> {
-        self.future.as_mut().poll(context)
+        self.locals.scope(|| self.future.as_mut().poll(context))
     }
 }
+
+/// Wraps a future into a task body reporting its output to a handle.
+///
+/// The body stops at its next poll once aborted through the handle, dropping
+/// the future.
+///
+/// # Parameters
+/// * `future` - The future run by the task.
+fn joinable<F>(future: F) -> (impl Future<Output = ()>, JoinHandle<F::Output>)
+where
+    F: Future,
+{
+    let state = JoinState::new();
+    let abort = AbortFlag::new();
+    let (task_state, task_abort) = (Arc::clone(&state), Arc::clone(&abort));
+    let mut future = Box::pin(future);
+    let body = poll_fn(move |cx| {
+        if task_abort.poll_aborted(cx) {
+            task_state.cancel();
+            return Poll::Ready(());
+        }
+        future
+            .as_mut()
+            .poll(cx)
+            .map(|output| task_state.finish(output))
+    });
+    (body, JoinHandle::new(state, abort))
+}
+
+/// Spawned tasks hand their output to their handle.
+#[test_case]
+fn join_task_output() {
+    use simple_executor::SimpleExecutor;
+
+    let (task, handle) = Task::with_handle(async { 6 * 7 });
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(Task::new(async move {
+        assert_eq!(handle.await, Ok(42), "wrong output");
+    }));
+    executor.spawn(task);
+    executor.run();
+}
+
+/// A handle knows when its task finished.
+#[test_case]
+fn task_is_finished() {
+    use core::time::Duration;
+    use simple_executor::SimpleExecutor;
+
+    let (task, handle) = Task::with_handle(timer::sleep(Duration::from_millis(10)));
+    assert!(!handle.is_finished(), "task finished before running");
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(task);
+    executor.run();
+    assert!(handle.is_finished(), "task did not finish");
+}
+
+/// An aborted task is dropped, and its joiner told so.
+#[test_case]
+fn abort_task() {
+    use futures_util::future::pending;
+    use simple_executor::SimpleExecutor;
+
+    let (task, handle) = Task::with_handle(pending::<()>());
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(task);
+    executor.spawn(Task::new(async move {
+        handle.abort_handle().abort();
+        assert_eq!(handle.await, Err(Cancelled), "aborted task finished");
+    }));
+    executor.run();
+}
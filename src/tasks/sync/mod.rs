@@ -0,0 +1,122 @@
+// File: src/tasks/sync/mod.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::collections::VecDeque;
+use core::{mem, task::Waker};
+
+pub use mutex::{Mutex, MutexGuard};
+pub use notify::{Notified, Notify};
+pub use rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+pub use semaphore::{Acquire, Semaphore, SemaphorePermit};
+
+/// Multi-producer, single-consumer channels.
+pub mod mpsc;
+/// Asynchronous mutual exclusion.
+mod mutex;
+/// Wake-up notifications.
+mod notify;
+/// Single value channels.
+pub mod oneshot;
+/// Asynchronous readers-writer lock.
+mod rwlock;
+/// Counting semaphore.
+mod semaphore;
+
+/// Tasks waiting on a primitive, in arrival order.
+///
+/// Every waiter gets an identifier, so that it can update its waker when
+/// polled again, and leave the queue when dropped.
+struct WaitQueue {
+    waiters: VecDeque<(u64, Waker)>,
+    next_id: u64,
+}
+
+impl WaitQueue {
+    /// An empty queue.
+    const fn new() -> Self {
+        Self {
+            waiters: VecDeque::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Adds a waiter at the back of the queue, or updates its waker.
+    ///
+    /// # Parameters
+    /// * `id` - The identifier of the waiter, set on its first registration,
+    /// * `waker` - Its waker.
+    fn register(&mut self, id: &mut Option<u64>, waker: &Waker) {
+        if let Some(id) = *id {
+            if let Some((_, registered)) = self.waiters.iter_mut().find(|(other, _)| *other == id) {
+                registered.clone_from(waker);
+                return;
+            }
+        }
+        let new_id = self.next_id;
+        self.next_id += 1;
+        self.waiters.push_back((new_id, waker.clone()));
+        *id = Some(new_id);
+    }
+
+    /// Removes a waiter.
+    ///
+    /// Returns whether it was still queued.
+    fn remove(&mut self, id: u64) -> bool {
+        let position = self.waiters.iter().position(|(other, _)| *other == id);
+        position
+            .and_then(|index| self.waiters.remove(index))
+            .is_some()
+    }
+
+    /// Whether a waiter is still queued.
+    fn contains(&self, id: u64) -> bool {
+        self.waiters.iter().any(|(other, _)| *other == id)
+    }
+
+    /// Identifier of the first waiter.
+    fn front(&self) -> Option<u64> {
+        self.waiters.front().map(|(id, _)| *id)
+    }
+
+    /// Wakes the first waiter, leaving it in the queue.
+    fn wake_front(&self) {
+        if let Some((_, waker)) = self.waiters.front() {
+            waker.wake_by_ref();
+        }
+    }
+
+    /// Removes the first waiter, returning its waker.
+    fn pop_front(&mut self) -> Option<Waker> {
+        self.waiters.pop_front().map(|(_, waker)| waker)
+    }
+
+    /// Removes every waiter, returning their wakers.
+    fn take_all(&mut self) -> VecDeque<(u64, Waker)> {
+        mem::take(&mut self.waiters)
+    }
+}
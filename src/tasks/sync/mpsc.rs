@@ -0,0 +1,478 @@
+// File: src/tasks/sync/mpsc.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::{collections::VecDeque, sync::Arc};
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use super::WaitQueue;
+use crate::spinlock::IrqSpinlock;
+
+/// Content of a channel, shared by all its ends.
+struct Chan<T> {
+    queue: VecDeque<T>,
+    /// Maximum number of queued values, if bounded.
+    capacity: Option<usize>,
+    senders: usize,
+    receiver_gone: bool,
+    /// Waker of the task waiting on the receiver.
+    receiver: Option<Waker>,
+    /// Senders waiting for room in a bounded channel, served in arrival
+    /// order.
+    waiting_senders: WaitQueue,
+}
+
+type Shared<T> = Arc<IrqSpinlock<Chan<T>>>;
+
+impl<T> Chan<T> {
+    /// Whether a value can be queued.
+    fn has_room(&self) -> bool {
+        self.capacity
+            .is_none_or(|capacity| self.queue.len() < capacity)
+    }
+
+    /// Queues a value, returning the waker of the receiver.
+    fn push(&mut self, value: T) -> Option<Waker> {
+        self.queue.push_back(value);
+        self.receiver.take()
+    }
+}
+
+/// Error returned when sending to a channel whose receiver was dropped.
+///
+/// It gives the value back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("receiver dropped")
+    }
+}
+
+/// Error returned by [`Sender::try_send`], giving the value back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The channel is full.
+    Full(T),
+    /// The receiver was dropped.
+    Closed(T),
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => formatter.write_str("channel full"),
+            Self::Closed(_) => formatter.write_str("receiver dropped"),
+        }
+    }
+}
+
+/// Creates a channel holding at most `capacity` values: senders wait for
+/// room once it is full.
+///
+/// # Parameters
+/// * `capacity` - Maximum number of queued values.
+///
+/// # Panics
+/// Panics if `capacity` is zero.
+#[must_use]
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "mpsc channel capacity must not be zero");
+    let chan = new_chan(Some(capacity));
+    (
+        Sender {
+            chan: Arc::clone(&chan),
+        },
+        Receiver { chan },
+    )
+}
+
+/// Creates a channel without capacity limit: sending never waits.
+#[must_use]
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, Receiver<T>) {
+    let chan = new_chan(None);
+    (
+        UnboundedSender {
+            chan: Arc::clone(&chan),
+        },
+        Receiver { chan },
+    )
+}
+
+/// Creates the shared content of a channel, with one sender.
+fn new_chan<T>(capacity: Option<usize>) -> Shared<T> {
+    Arc::new(IrqSpinlock::new(Chan {
+        queue: VecDeque::new(),
+        capacity,
+        senders: 1,
+        receiver_gone: false,
+        receiver: None,
+        waiting_senders: WaitQueue::new(),
+    }))
+}
+
+/// Registers a new sender of a channel.
+fn clone_sender<T>(chan: &Shared<T>) -> Shared<T> {
+    chan.lock().senders += 1;
+    Arc::clone(chan)
+}
+
+/// Unregisters a sender, waking the receiver once the last one is gone.
+fn drop_sender<T>(chan: &Shared<T>) {
+    let mut chan = chan.lock();
+    chan.senders -= 1;
+    let receiver = if chan.senders == 0 {
+        chan.receiver.take()
+    } else {
+        None
+    };
+    drop(chan);
+    if let Some(waker) = receiver {
+        waker.wake();
+    }
+}
+
+/// Sending end of a bounded [`channel`].
+pub struct Sender<T> {
+    chan: Shared<T>,
+}
+
+impl<T> Sender<T> {
+    /// Sends a value, waiting for room in the channel.
+    ///
+    /// # Parameters
+    /// * `value` - The value to send.
+    pub const fn send(&self, value: T) -> Sending<'_, T> {
+        Sending {
+            chan: &self.chan,
+            value: Some(value),
+            waiter: None,
+        }
+    }
+
+    /// Sends a value if there is room in the channel and no sender is
+    /// waiting.
+    ///
+    /// # Parameters
+    /// * `value` - The value to send.
+    ///
+    /// # Errors
+    /// Gives the value back if the channel is full or its receiver dropped.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut chan = self.chan.lock();
+        if chan.receiver_gone {
+            return Err(TrySendError::Closed(value));
+        }
+        if !chan.has_room() || chan.waiting_senders.front().is_some() {
+            return Err(TrySendError::Full(value));
+        }
+        let receiver = chan.push(value);
+        drop(chan);
+        if let Some(waker) = receiver {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Whether the receiver was dropped.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.chan.lock().receiver_gone
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            chan: clone_sender(&self.chan),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        drop_sender(&self.chan);
+    }
+}
+
+/// Future returned by [`Sender::send`].
+pub struct Sending<'sender, T> {
+    chan: &'sender Shared<T>,
+    /// The value, until sent.
+    value: Option<T>,
+    /// Place in the waiting senders queue, once registered.
+    waiter: Option<u64>,
+}
+
+// The value is never pinned: it is moved into the channel.
+impl<T> Unpin for Sending<'_, T> {}
+
+impl<T> Future for Sending<'_, T> {
+    type Output = Result<(), SendError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let Some(value) = this.value.take() else {
+            return Poll::Ready(Ok(()));
+        };
+        let mut chan = this.chan.lock();
+        if chan.receiver_gone {
+            return Poll::Ready(Err(SendError(value)));
+        }
+        let is_first = chan
+            .waiting_senders
+            .front()
+            .is_none_or(|id| Some(id) == this.waiter);
+        if !is_first || !chan.has_room() {
+            this.value = Some(value);
+            chan.waiting_senders.register(&mut this.waiter, cx.waker());
+            return Poll::Pending;
+        }
+        if let Some(id) = this.waiter.take() {
+            chan.waiting_senders.remove(id);
+        }
+        let receiver = chan.push(value);
+        // the next sender may use the room left
+        if chan.has_room() {
+            chan.waiting_senders.wake_front();
+        }
+        drop(chan);
+        if let Some(waker) = receiver {
+            waker.wake();
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Drop for Sending<'_, T> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiter else {
+            return;
+        };
+        let mut chan = self.chan.lock();
+        let was_first = chan.waiting_senders.front() == Some(id);
+        chan.waiting_senders.remove(id);
+        // pass on a wake-up for room this sender did not use
+        if was_first && chan.has_room() {
+            chan.waiting_senders.wake_front();
+        }
+    }
+}
+
+/// Sending end of an [`unbounded_channel`].
+pub struct UnboundedSender<T> {
+    chan: Shared<T>,
+}
+
+impl<T> UnboundedSender<T> {
+    /// Sends a value, without waiting.
+    ///
+    /// # Parameters
+    /// * `value` - The value to send.
+    ///
+    /// # Errors
+    /// Gives the value back if the receiver was dropped.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut chan = self.chan.lock();
+        if chan.receiver_gone {
+            return Err(SendError(value));
+        }
+        let receiver = chan.push(value);
+        drop(chan);
+        if let Some(waker) = receiver {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Whether the receiver was dropped.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.chan.lock().receiver_gone
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            chan: clone_sender(&self.chan),
+        }
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        drop_sender(&self.chan);
+    }
+}
+
+/// Receiving end of a [`channel`] or an [`unbounded_channel`].
+pub struct Receiver<T> {
+    chan: Shared<T>,
+}
+
+impl<T> Receiver<T> {
+    /// Waits for the next value.
+    ///
+    /// Returns `None` once every sender was dropped and the channel emptied.
+    pub const fn recv(&mut self) -> Recv<'_, T> {
+        Recv { receiver: self }
+    }
+
+    /// Polls for the next value.
+    ///
+    /// # Parameters
+    /// * `cx` - Context of the polling task, woken when a value arrives.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut chan = self.chan.lock();
+        if let Some(value) = chan.queue.pop_front() {
+            chan.waiting_senders.wake_front();
+            return Poll::Ready(Some(value));
+        }
+        if chan.senders == 0 {
+            return Poll::Ready(None);
+        }
+        chan.receiver = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Takes the next value if there is one.
+    pub fn try_recv(&mut self) -> Option<T> {
+        let mut chan = self.chan.lock();
+        let value = chan.queue.pop_front()?;
+        chan.waiting_senders.wake_front();
+        Some(value)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut chan = self.chan.lock();
+        chan.receiver_gone = true;
+        let senders = chan.waiting_senders.take_all();
+        drop(chan);
+        for (_, waker) in senders {
+            waker.wake();
+        }
+    }
+}
+
+/// Future returned by [`Receiver::recv`].
+pub struct Recv<'receiver, T> {
+    receiver: &'receiver mut Receiver<T>,
+}
+
+impl<T> Future for Recv<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().receiver.poll_recv(cx)
+    }
+}
+
+/// Senders wait for room in a bounded channel, and the receiver sees the end.
+#[test_case]
+fn bounded_channel_waits_for_room() {
+    use super::super::{simple_executor::SimpleExecutor, Task};
+    use alloc::vec::Vec;
+
+    let (sender, mut receiver) = channel(1);
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(Task::new(async move {
+        for value in 0..4 {
+            assert!(sender.send(value).await.is_ok(), "receiver dropped");
+        }
+        assert!(
+            matches!(sender.try_send(4), Err(TrySendError::Full(4))),
+            "channel not full"
+        );
+    }));
+    executor.spawn(Task::new(async move {
+        let mut values = Vec::new();
+        while let Some(value) = receiver.recv().await {
+            values.push(value);
+        }
+        assert_eq!(values, [0, 1, 2, 3], "values lost");
+    }));
+    executor.run();
+}
+
+/// Room freed by the receiver goes to the first waiting sender, not to a
+/// later one.
+#[test_case]
+fn waiting_senders_are_served_in_order() {
+    use core::task::Waker;
+
+    let (sender, mut receiver) = channel(1);
+    let mut context = Context::from_waker(Waker::noop());
+    assert!(sender.try_send(0).is_ok(), "empty channel full");
+    let mut first = sender.send(1);
+    assert!(
+        Pin::new(&mut first).poll(&mut context).is_pending(),
+        "sent to a full channel"
+    );
+    assert_eq!(receiver.try_recv(), Some(0), "value lost");
+    let mut late = sender.send(2);
+    assert!(
+        Pin::new(&mut late).poll(&mut context).is_pending(),
+        "late sender took the room of a waiting one"
+    );
+    assert!(
+        matches!(sender.try_send(3), Err(TrySendError::Full(3))),
+        "sent ahead of waiting senders"
+    );
+    assert!(
+        Pin::new(&mut first).poll(&mut context).is_ready(),
+        "first sender still waiting"
+    );
+    assert_eq!(receiver.try_recv(), Some(1), "wrong order");
+    assert!(
+        Pin::new(&mut late).poll(&mut context).is_ready(),
+        "late sender still waiting"
+    );
+    assert_eq!(receiver.try_recv(), Some(2), "wrong order");
+}
+
+/// Values sent without waiting are received in order.
+#[test_case]
+fn unbounded_channel_keeps_order() {
+    let (sender, mut receiver) = unbounded_channel();
+    for value in 0..16 {
+        assert!(sender.send(value).is_ok(), "receiver dropped");
+    }
+    drop(sender);
+    for value in 0..16 {
+        assert_eq!(receiver.try_recv(), Some(value), "wrong order");
+    }
+    drop(receiver);
+}
@@ -0,0 +1,166 @@
+// File: src/tasks/sync/mutex.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use super::Semaphore;
+
+/// An asynchronous mutual exclusion lock.
+///
+/// Unlike a spinlock, a task waiting for the lock yields to the executor
+/// instead of spinning, so the lock can be held across `.await` points.
+pub struct Mutex<T: ?Sized> {
+    semaphore: Semaphore,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY:
+// The semaphore gives access to the data to one task at a time.
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+// SAFETY:
+// The semaphore gives access to the data to one task at a time.
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    /// Creates an unlocked mutex.
+    ///
+    /// # Parameters
+    /// * `data` - The protected data.
+    pub const fn new(data: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(1),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the mutex, returning the protected data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Waits until the lock is acquired.
+    ///
+    /// Tasks acquire the lock in the order they asked for it.
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        self.semaphore.acquire().await.forget();
+        MutexGuard { mutex: self }
+    }
+
+    /// Acquires the lock if it is free.
+    #[must_use]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.semaphore.try_acquire().map(|permit| {
+            permit.forget();
+            MutexGuard { mutex: self }
+        })
+    }
+
+    /// Mutable access to the data, statically known to be unlocked.
+    pub const fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mutex")
+            .field("locked", &(self.semaphore.available_permits() == 0))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Access to the data of a locked [`Mutex`], unlocking it when dropped.
+pub struct MutexGuard<'mutex, T: ?Sized> {
+    mutex: &'mutex Mutex<T>,
+}
+
+// SAFETY:
+// Sharing the guard only gives shared access to the data.
+unsafe impl<T: ?Sized + Sync> Sync for MutexGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY:
+        // The guard holds the only permit of the mutex.
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY:
+        // The guard holds the only permit of the mutex.
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.semaphore.add_permits(1);
+    }
+}
+
+/// The lock is held across `.await` points without losing updates.
+#[test_case]
+fn lock_across_await() {
+    use super::super::{simple_executor::SimpleExecutor, timer, Task};
+    use alloc::rc::Rc;
+    use core::time::Duration;
+
+    let counter = Rc::new(Mutex::new(0));
+    let mut executor = SimpleExecutor::new();
+    for _ in 0..3 {
+        let counter = Rc::clone(&counter);
+        executor.spawn(Task::new(async move {
+            let mut value = counter.lock().await;
+            let read = *value;
+            timer::sleep(Duration::from_millis(1)).await;
+            *value = read + 1;
+        }));
+    }
+    executor.run();
+    assert_eq!(
+        counter.try_lock().map(|value| *value),
+        Some(3),
+        "lost update"
+    );
+}
@@ -0,0 +1,236 @@
+// File: src/tasks/sync/notify.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::vec::Vec;
+use core::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::WaitQueue;
+use crate::spinlock::IrqSpinlock;
+
+/// Waiters of a [`Notify`], and whether a notification is pending.
+struct State {
+    waiters: WaitQueue,
+    permit: bool,
+    /// Waiters woken by [`Notify::notify_one`] that did not see it yet.
+    ///
+    /// A waiter dropped before seeing its notification passes it on only if
+    /// it was meant for a single waiter.
+    woken_alone: Vec<u64>,
+}
+
+/// Wakes tasks waiting for an event.
+///
+/// Notifying is cheap and does not block, so it can be done from an
+/// interrupt handler.
+pub struct Notify {
+    state: IrqSpinlock<State>,
+}
+
+impl Notify {
+    /// Creates a notifier without pending notification.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            state: IrqSpinlock::new(State {
+                waiters: WaitQueue::new(),
+                permit: false,
+                woken_alone: Vec::new(),
+            }),
+        }
+    }
+
+    /// Waits for a notification.
+    pub const fn notified(&self) -> Notified<'_> {
+        Notified {
+            notify: self,
+            waiter: None,
+            done: false,
+        }
+    }
+
+    /// Wakes the first waiting task.
+    ///
+    /// Without waiting task, the notification is kept for the next call to
+    /// [`Self::notified`]. Notifications do not add up: at most one is kept.
+    pub fn notify_one(&self) {
+        let mut state = self.state.lock();
+        if let Some(id) = state.waiters.front() {
+            state.woken_alone.push(id);
+        } else {
+            state.permit = true;
+        }
+        let waiter = state.waiters.pop_front();
+        drop(state);
+        if let Some(waker) = waiter {
+            waker.wake();
+        }
+    }
+
+    /// Wakes every waiting task.
+    ///
+    /// Nothing is kept for tasks waiting later on.
+    pub fn notify_waiters(&self) {
+        let waiters = self.state.lock().waiters.take_all();
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Future returned by [`Notify::notified`].
+pub struct Notified<'notify> {
+    notify: &'notify Notify,
+    /// Place in the waiters queue, once registered.
+    waiter: Option<u64>,
+    done: bool,
+}
+
+impl Future for Notified<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(());
+        }
+        let mut state = this.notify.state.lock();
+        // a notifier removes the waiters it wakes from the queue
+        let notified = match this.waiter {
+            Some(id) => !state.waiters.contains(id),
+            None => mem::take(&mut state.permit),
+        };
+        if notified {
+            if let Some(id) = this.waiter {
+                state.woken_alone.retain(|&woken| woken != id);
+            }
+            this.done = true;
+            return Poll::Ready(());
+        }
+        state.waiters.register(&mut this.waiter, cx.waker());
+        Poll::Pending
+    }
+}
+
+impl Drop for Notified<'_> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiter.filter(|_| !self.done) else {
+            return;
+        };
+        let mut state = self.notify.state.lock();
+        let was_woken_alone = !state.waiters.remove(id) && {
+            let count = state.woken_alone.len();
+            state.woken_alone.retain(|&woken| woken != id);
+            state.woken_alone.len() < count
+        };
+        drop(state);
+        // pass on a single waiter notification nobody observed; broadcasts
+        // reached every waiter already
+        if was_woken_alone {
+            self.notify.notify_one();
+        }
+    }
+}
+
+/// A notification sent before waiting is kept, a later one wakes the waiter.
+#[test_case]
+fn notify_one_wakes_waiter() {
+    use super::super::{simple_executor::SimpleExecutor, Task};
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+
+    let notify = Rc::new(Notify::new());
+    let woken = Rc::new(Cell::new(0));
+    notify.notify_one();
+    let mut executor = SimpleExecutor::new();
+    let (waiter, count) = (Rc::clone(&notify), Rc::clone(&woken));
+    executor.spawn(Task::new(async move {
+        waiter.notified().await;
+        count.set(1);
+        waiter.notified().await;
+        count.set(2);
+    }));
+    let notifier = Rc::clone(&notify);
+    executor.spawn(Task::new(async move {
+        notifier.notify_one();
+    }));
+    executor.run();
+    assert_eq!(woken.get(), 2, "notification not delivered");
+}
+
+/// An unobserved single notification is passed on, an unobserved broadcast
+/// is not.
+#[test_case]
+fn dropped_waiters_pass_on_single_notifications() {
+    use alloc::boxed::Box;
+    use core::task::Waker;
+
+    let notify = Notify::new();
+    let mut context = Context::from_waker(Waker::noop());
+    let mut poll = |notified: &mut Pin<Box<Notified<'_>>>| notified.as_mut().poll(&mut context);
+
+    let mut first = Box::pin(notify.notified());
+    let mut second = Box::pin(notify.notified());
+    assert!(
+        poll(&mut first).is_pending(),
+        "notified without notification"
+    );
+    assert!(
+        poll(&mut second).is_pending(),
+        "notified without notification"
+    );
+    notify.notify_one();
+    drop(first);
+    assert!(poll(&mut second).is_ready(), "single notification lost");
+
+    let mut waiting = Box::pin(notify.notified());
+    let mut other = Box::pin(notify.notified());
+    assert!(
+        poll(&mut waiting).is_pending(),
+        "notified without notification"
+    );
+    assert!(
+        poll(&mut other).is_pending(),
+        "notified without notification"
+    );
+    notify.notify_waiters();
+    drop(waiting);
+    assert!(poll(&mut other).is_ready(), "broadcast lost");
+    let mut later = Box::pin(notify.notified());
+    assert!(poll(&mut later).is_pending(), "broadcast passed on");
+}
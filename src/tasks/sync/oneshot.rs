@@ -0,0 +1,175 @@
+// File: src/tasks/sync/oneshot.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::sync::Arc;
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::spinlock::IrqSpinlock;
+
+/// Content of the channel, shared by both ends.
+struct State<T> {
+    value: Option<T>,
+    /// Whether the sender was used or dropped.
+    sender_gone: bool,
+    receiver_gone: bool,
+    /// Waker of the task waiting on the receiver.
+    receiver: Option<Waker>,
+}
+
+/// Error returned by a [`Receiver`] when the sender was dropped without
+/// sending a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("sender dropped")
+    }
+}
+
+/// Creates a channel carrying a single value.
+#[must_use]
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let state = Arc::new(IrqSpinlock::new(State {
+        value: None,
+        sender_gone: false,
+        receiver_gone: false,
+        receiver: None,
+    }));
+    (
+        Sender {
+            state: Arc::clone(&state),
+        },
+        Receiver { state },
+    )
+}
+
+/// Sending end of a [`channel`].
+pub struct Sender<T> {
+    state: Arc<IrqSpinlock<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends the value, waking the receiver.
+    ///
+    /// # Errors
+    /// Gives the value back if the receiver was dropped.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let mut state = self.state.lock();
+        if state.receiver_gone {
+            return Err(value);
+        }
+        state.value = Some(value);
+        Ok(())
+    }
+
+    /// Whether the receiver was dropped.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().receiver_gone
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock();
+        state.sender_gone = true;
+        let receiver = state.receiver.take();
+        drop(state);
+        if let Some(waker) = receiver {
+            waker.wake();
+        }
+    }
+}
+
+/// Receiving end of a [`channel`]: a future resolving to the value sent, or
+/// to [`RecvError`] if the sender was dropped first.
+pub struct Receiver<T> {
+    state: Arc<IrqSpinlock<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Takes the value if it was already sent.
+    ///
+    /// # Errors
+    /// Fails if the sender was dropped without sending a value.
+    pub fn try_recv(&mut self) -> Result<Option<T>, RecvError> {
+        let mut state = self.state.lock();
+        match state.value.take() {
+            Some(value) => Ok(Some(value)),
+            None if state.sender_gone => Err(RecvError),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        if let Some(value) = state.value.take() {
+            return Poll::Ready(Ok(value));
+        }
+        if state.sender_gone {
+            return Poll::Ready(Err(RecvError));
+        }
+        state.receiver = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.state.lock().receiver_gone = true;
+    }
+}
+
+/// The value sent reaches the receiver, a dropped sender is reported.
+#[test_case]
+fn send_and_drop() {
+    use super::super::{simple_executor::SimpleExecutor, Task};
+
+    let (sender, receiver) = channel();
+    let (dropped, closed) = channel::<u8>();
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(Task::new(async move {
+        assert_eq!(receiver.await, Ok(42), "value lost");
+        assert_eq!(closed.await, Err(RecvError), "sender drop not reported");
+    }));
+    executor.spawn(Task::new(async move {
+        assert!(sender.send(42).is_ok(), "receiver dropped");
+        drop(dropped);
+    }));
+    executor.run();
+}
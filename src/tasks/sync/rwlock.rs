@@ -0,0 +1,193 @@
+// File: src/tasks/sync/rwlock.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use super::Semaphore;
+
+/// Maximum number of concurrent readers.
+///
+/// A writer takes all of these permits at once.
+const MAX_READERS: usize = 1 << 29;
+
+/// An asynchronous readers-writer lock.
+///
+/// Access is granted in arrival order, so that a stream of readers cannot
+/// starve a writer.
+pub struct RwLock<T: ?Sized> {
+    semaphore: Semaphore,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY:
+// The semaphore gives access to the data to one writer at a time.
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+// SAFETY:
+// Readers share the data across tasks, so it has to be `Sync` too.
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    /// Creates an unlocked readers-writer lock.
+    ///
+    /// # Parameters
+    /// * `data` - The protected data.
+    pub const fn new(data: T) -> Self {
+        Self {
+            semaphore: Semaphore::new(MAX_READERS),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Consumes the lock, returning the protected data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Waits for shared read access.
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.semaphore.acquire().await.forget();
+        RwLockReadGuard { lock: self }
+    }
+
+    /// Waits for exclusive write access.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.semaphore.acquire_many(MAX_READERS).await.forget();
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Mutable access to the data, statically known to be unlocked.
+    pub const fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RwLock")
+            .field(
+                "readers",
+                &(MAX_READERS - self.semaphore.available_permits()),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+/// Shared access to the data of a [`RwLock`].
+pub struct RwLockReadGuard<'lock, T: ?Sized> {
+    lock: &'lock RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY:
+        // No writer holds the lock while a reader does.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.add_permits(1);
+    }
+}
+
+/// Exclusive access to the data of a [`RwLock`].
+pub struct RwLockWriteGuard<'lock, T: ?Sized> {
+    lock: &'lock RwLock<T>,
+}
+
+// SAFETY:
+// Sharing the guard only gives shared access to the data.
+unsafe impl<T: ?Sized + Sync> Sync for RwLockWriteGuard<'_, T> {}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY:
+        // The writer holds every permit of the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY:
+        // The writer holds every permit of the lock.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.semaphore.add_permits(MAX_READERS);
+    }
+}
+
+/// Readers share the lock, a writer waits for all of them.
+#[test_case]
+fn readers_then_writer() {
+    use super::super::{simple_executor::SimpleExecutor, timer, Task};
+    use alloc::rc::Rc;
+    use core::time::Duration;
+
+    let lock = Rc::new(RwLock::new(0));
+    let mut executor = SimpleExecutor::new();
+    for _ in 0..2 {
+        let lock = Rc::clone(&lock);
+        executor.spawn(Task::new(async move {
+            let value = lock.read().await;
+            timer::sleep(Duration::from_millis(5)).await;
+            assert_eq!(*value, 0, "written while read");
+        }));
+    }
+    let writer = Rc::clone(&lock);
+    executor.spawn(Task::new(async move {
+        *writer.write().await += 1;
+    }));
+    executor.run();
+    assert_eq!(
+        Rc::into_inner(lock).map(RwLock::into_inner),
+        Some(1),
+        "write lost"
+    );
+}
@@ -0,0 +1,211 @@
+// File: src/tasks/sync/semaphore.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::WaitQueue;
+use crate::spinlock::IrqSpinlock;
+
+/// Permits and waiters of a [`Semaphore`].
+struct State {
+    permits: usize,
+    waiters: WaitQueue,
+}
+
+/// An asynchronous counting semaphore.
+///
+/// Permits are handed out in arrival order: a task asking for many permits
+/// is not overtaken by later tasks asking for fewer.
+pub struct Semaphore {
+    state: IrqSpinlock<State>,
+}
+
+impl Semaphore {
+    /// Creates a semaphore.
+    ///
+    /// # Parameters
+    /// * `permits` - Number of permits initially available.
+    #[must_use]
+    pub const fn new(permits: usize) -> Self {
+        Self {
+            state: IrqSpinlock::new(State {
+                permits,
+                waiters: WaitQueue::new(),
+            }),
+        }
+    }
+
+    /// Number of permits currently available.
+    #[must_use]
+    pub fn available_permits(&self) -> usize {
+        self.state.lock().permits
+    }
+
+    /// Waits for a permit.
+    pub const fn acquire(&self) -> Acquire<'_> {
+        self.acquire_many(1)
+    }
+
+    /// Waits for several permits, taken all at once.
+    ///
+    /// # Parameters
+    /// * `permits` - Number of permits to take.
+    pub const fn acquire_many(&self, permits: usize) -> Acquire<'_> {
+        Acquire {
+            semaphore: self,
+            permits,
+            waiter: None,
+        }
+    }
+
+    /// Takes a permit if one is available and nobody is waiting.
+    #[must_use]
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let mut state = self.state.lock();
+        (state.waiters.front().is_none() && state.permits >= 1).then(|| {
+            state.permits -= 1;
+            SemaphorePermit {
+                semaphore: self,
+                permits: 1,
+            }
+        })
+    }
+
+    /// Adds permits, waking the first waiter.
+    ///
+    /// # Parameters
+    /// * `permits` - Number of permits to add.
+    pub fn add_permits(&self, permits: usize) {
+        let mut state = self.state.lock();
+        state.permits += permits;
+        state.waiters.wake_front();
+    }
+}
+
+/// Future returned by [`Semaphore::acquire`] and [`Semaphore::acquire_many`].
+pub struct Acquire<'semaphore> {
+    semaphore: &'semaphore Semaphore,
+    permits: usize,
+    /// Place in the waiters queue, once registered.
+    waiter: Option<u64>,
+}
+
+impl<'semaphore> Future for Acquire<'semaphore> {
+    type Output = SemaphorePermit<'semaphore>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut state = this.semaphore.state.lock();
+        let is_first = state
+            .waiters
+            .front()
+            .is_none_or(|id| Some(id) == this.waiter);
+        if is_first && state.permits >= this.permits {
+            state.permits -= this.permits;
+            if let Some(id) = this.waiter.take() {
+                state.waiters.remove(id);
+            }
+            // the next waiter may be satisfied by what is left
+            if state.permits > 0 {
+                state.waiters.wake_front();
+            }
+            return Poll::Ready(SemaphorePermit {
+                semaphore: this.semaphore,
+                permits: this.permits,
+            });
+        }
+        state.waiters.register(&mut this.waiter, cx.waker());
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter {
+            let mut state = self.semaphore.state.lock();
+            let was_first = state.waiters.front() == Some(id);
+            state.waiters.remove(id);
+            if was_first {
+                state.waiters.wake_front();
+            }
+        }
+    }
+}
+
+/// Permits taken from a [`Semaphore`], given back when dropped.
+pub struct SemaphorePermit<'semaphore> {
+    semaphore: &'semaphore Semaphore,
+    permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+    /// Keeps the permits taken: they are not given back.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.add_permits(self.permits);
+        }
+    }
+}
+
+/// Permits are handed out in arrival order.
+#[test_case]
+fn permits_are_fair() {
+    use super::super::{simple_executor::SimpleExecutor, timer, Task};
+    use alloc::{rc::Rc, vec::Vec};
+    use core::{cell::RefCell, time::Duration};
+
+    let semaphore = Rc::new(Semaphore::new(0));
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let mut executor = SimpleExecutor::new();
+    for (index, permits) in [(0, 2), (1, 1)] {
+        let (semaphore, order) = (Rc::clone(&semaphore), Rc::clone(&order));
+        executor.spawn(Task::new(async move {
+            let _permit = semaphore.acquire_many(permits).await;
+            order.borrow_mut().push(index);
+        }));
+    }
+    let releaser = Rc::clone(&semaphore);
+    executor.spawn(Task::new(async move {
+        releaser.add_permits(1);
+        timer::sleep(Duration::from_millis(10)).await;
+        releaser.add_permits(1);
+    }));
+    executor.run();
+    assert_eq!(*order.borrow(), [0, 1], "waiter overtaken");
+    assert_eq!(semaphore.available_permits(), 2, "permits lost");
+}
@@ -0,0 +1,275 @@
+// File: src/tasks/timer.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::{boxed::Box, collections::BinaryHeap, sync::Arc};
+use core::{
+    cmp::{Ordering, Reverse},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::atomic::{self, AtomicU64},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::task::AtomicWaker;
+
+pub use crate::interrupts::pit::{ticks, TIMER_FREQUENCY};
+use crate::spinlock::IrqSpinlock;
+
+/// Pending timers, ordered by deadline.
+static TIMERS: IrqSpinlock<BinaryHeap<Reverse<TimerEntry>>> = IrqSpinlock::new(BinaryHeap::new());
+
+/// A registered deadline, waiting in [`TIMERS`].
+struct TimerEntry {
+    /// Tick at which the timer expires.
+    deadline: u64,
+    /// Unique identifier of the timer (breaks ties between equal deadlines).
+    id: u64,
+    /// Waker of the task waiting on the timer.
+    waker: Arc<AtomicWaker>,
+}
+
+impl TimerEntry {
+    const fn key(&self) -> (u64, u64) {
+        (self.deadline, self.id)
+    }
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for TimerEntry {}
+
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Called by the timer interrupt handler.
+///
+/// Wakes every task whose deadline is reached. Must not block or allocate.
+///
+/// # Parameters
+/// * `now` - The current tick count.
+pub(crate) fn wake_expired(now: u64) {
+    let mut timers = TIMERS.lock();
+    while timers
+        .peek()
+        .is_some_and(|Reverse(entry)| entry.deadline <= now)
+    {
+        if let Some(Reverse(entry)) = timers.pop() {
+            entry.waker.wake();
+        }
+    }
+}
+
+/// Converts a duration into a number of timer ticks, rounding up.
+///
+/// # Parameters
+/// * `duration` - The duration to convert.
+pub(crate) fn duration_to_ticks(duration: Duration) -> u64 {
+    let ticks = (duration.as_nanos() * u128::from(TIMER_FREQUENCY)).div_ceil(1_000_000_000);
+    u64::try_from(ticks).unwrap_or(u64::MAX)
+}
+
+/// Future completing once the given duration has elapsed.
+///
+/// # Parameters
+/// * `duration` - How long to sleep for.
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep::until(ticks().saturating_add(duration_to_ticks(duration)))
+}
+
+/// Runs `future`, giving up if it did not complete within `duration`.
+///
+/// # Parameters
+/// * `duration` - Maximum time given to the future,
+/// * `future` - The future to run.
+pub fn timeout<F>(duration: Duration, future: F) -> Timeout<F>
+where
+    F: Future,
+{
+    Timeout {
+        future: Box::pin(future),
+        sleep: sleep(duration),
+    }
+}
+
+/// Future returned by [`sleep`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Sleep {
+    /// Tick at which the future completes.
+    deadline: u64,
+    /// Identifier and waker of the timer, once registered in the timer queue.
+    registration: Option<(u64, Arc<AtomicWaker>)>,
+}
+
+impl Sleep {
+    /// Sleeps until the given tick.
+    ///
+    /// # Parameters
+    /// * `deadline` - Tick (as returned by [`ticks`]) at which to wake up.
+    pub const fn until(deadline: u64) -> Self {
+        Self {
+            deadline,
+            registration: None,
+        }
+    }
+
+    /// Tick at which the future completes.
+    #[must_use]
+    pub const fn deadline(&self) -> u64 {
+        self.deadline
+    }
+
+    fn is_elapsed(&self) -> bool {
+        ticks() >= self.deadline
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = self.get_mut();
+        if this.is_elapsed() {
+            return Poll::Ready(());
+        }
+
+        if let Some((_, waker)) = &this.registration {
+            waker.register(cx.waker());
+        } else {
+            static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+            let id = NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed);
+            let waker = Arc::new(AtomicWaker::new());
+            waker.register(cx.waker());
+            let entry = TimerEntry {
+                deadline: this.deadline,
+                id,
+                waker: Arc::clone(&waker),
+            };
+            TIMERS.lock().push(Reverse(entry));
+            this.registration = Some((id, waker));
+        }
+
+        // the deadline may have passed while registering
+        if this.is_elapsed() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        // the interrupt handler must never be the one freeing the waker
+        if let Some((id, _)) = self.registration.take() {
+            TIMERS.lock().retain(|Reverse(entry)| entry.id != id);
+        }
+    }
+}
+
+/// Error returned by [`Timeout`] when the deadline elapsed first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadline has elapsed")
+    }
+}
+
+/// Future returned by [`timeout`].
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<F: Future> {
+    future: Pin<Box<F>>,
+    sleep: Sleep,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Poll::Ready(output) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        Pin::new(&mut this.sleep).poll(cx).map(|()| Err(Elapsed))
+    }
+}
+
+/// A sleeping task is not woken before its deadline.
+#[test_case]
+fn sleep_waits_for_deadline() {
+    use super::{simple_executor::SimpleExecutor, Task};
+
+    let start = ticks();
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(Task::new(sleep(Duration::from_millis(50))));
+    executor.run();
+    assert!(ticks() >= start + 5, "woke up too early");
+}
+
+/// A future that never completes is cut short by its timeout.
+#[test_case]
+fn timeout_elapses() {
+    use super::{simple_executor::SimpleExecutor, Task};
+    use futures_util::future::pending;
+
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(Task::new(async {
+        let result = timeout(Duration::from_millis(20), pending::<()>()).await;
+        assert_eq!(result, Err(Elapsed), "pending future did not time out");
+    }));
+    executor.run();
+}
+
+/// A future completing in time yields its output.
+#[test_case]
+fn timeout_completes() {
+    use super::{simple_executor::SimpleExecutor, Task};
+
+    let mut executor = SimpleExecutor::new();
+    executor.spawn(Task::new(async {
+        let result = timeout(Duration::from_secs(1), async { 42 }).await;
+        assert_eq!(result, Ok(42), "ready future timed out");
+    }));
+    executor.run();
+}
@@ -26,9 +26,14 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use core::any::type_name;
+use alloc::boxed::Box;
+use core::{any::type_name, future::Future, pin::Pin, time::Duration};
 
-use crate::{exit_qemu, serial_print, serial_println, QemuExitCode};
+use crate::{
+    exit_qemu, serial_print, serial_println,
+    tasks::{executor::Executor, timer},
+    QemuExitCode,
+};
 
 /// Testing driver for the kernel. Will be called externally.
 ///
@@ -71,3 +76,93 @@ where
         serial_println!("\r\x1B[32m{:<100}[Ok]\x1B[0m", type_name::<T>());
     }
 }
+
+/// Declares an asynchronous test case, written as an `async fn` without
+/// parameters.
+///
+/// The test runs on a fresh [`Executor`]. An optional `timeout = duration;`
+/// line before the function makes it fail if it did not complete in time;
+/// `Duration` is in scope for that expression.
+#[macro_export]
+macro_rules! async_test {
+    (
+        timeout = $timeout:expr;
+        $(#[$attr:meta])*
+        async fn $name:ident() $body:block
+    ) => {
+        $crate::async_test!(@declare $name, Some($timeout), $(#[$attr])* $body);
+    };
+    (
+        $(#[$attr:meta])*
+        async fn $name:ident() $body:block
+    ) => {
+        $crate::async_test!(@declare $name, None, $(#[$attr])* $body);
+    };
+    (@declare $name:ident, $timeout:expr, $(#[$attr:meta])* $body:block) => {
+        $(#[$attr])*
+        #[test_case]
+        static $name: $crate::AsyncTest = $crate::AsyncTest {
+            name: concat!(module_path!(), "::", stringify!($name)),
+            test: || {
+                async fn test() $body
+                $crate::AsyncTest::boxed(test())
+            },
+            timeout: {
+                use core::time::Duration;
+                $timeout
+            },
+        };
+    };
+}
+
+/// Creates the future running an asynchronous test.
+pub type AsyncTestFn = fn() -> Pin<Box<dyn Future<Output = ()>>>;
+
+/// An asynchronous test case, declared with [`async_test!`](crate::async_test).
+pub struct AsyncTest {
+    /// Name shown by the runner.
+    pub name: &'static str,
+    /// Creates the future running the test.
+    pub test: AsyncTestFn,
+    /// Time after which the test fails, if any.
+    pub timeout: Option<Duration>,
+}
+
+impl AsyncTest {
+    /// Boxes the future of a test declared with [`async_test!`](crate::async_test).
+    ///
+    /// # Parameters
+    /// * `future` - The future running the test.
+    #[doc(hidden)]
+    pub fn boxed<F>(future: F) -> Pin<Box<dyn Future<Output = ()>>>
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        Box::pin(future)
+    }
+}
+
+impl Testable for AsyncTest {
+    fn run(&self) {
+        self.start();
+        let mut executor = Executor::new();
+        let future = (self.test)();
+        if let Some(duration) = self.timeout {
+            assert!(
+                executor.block_on(timer::timeout(duration, future)).is_ok(),
+                "test timed out after {duration:?}"
+            );
+        } else {
+            executor.block_on(future);
+        }
+        self.success();
+    }
+
+    fn start(&self) {
+        serial_print!("{:<100}...", self.name);
+    }
+
+    fn success(&self) {
+        serial_println!("\r\x1B[32m{:<100}[Ok]\x1B[0m", self.name);
+    }
+}
@@ -0,0 +1,488 @@
+// File: src/thread/mod.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt, mem,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use x86_64::instructions::{hlt, interrupts};
+
+use crate::{
+    spinlock::IrqSpinlock,
+    syscall,
+    tasks::{
+        local::{self, SavedLocals},
+        timer::{duration_to_ticks, ticks},
+    },
+};
+
+/// Context switching.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+mod switch;
+
+/// Size of the stack of each thread, in 64 bits words (64 KiB).
+const STACK_WORDS: usize = 8 * 1024;
+/// Size of the stack system calls of each thread run on, in 64 bits words
+/// (20 KiB).
+const SYSCALL_STACK_WORDS: usize = 2560;
+
+/// Entry point of a thread, as handed to the new thread.
+type Entry = Box<dyn FnOnce() + Send>;
+
+/// The thread scheduler, once initialized.
+static SCHEDULER: IrqSpinlock<Option<Scheduler>> = IrqSpinlock::new(None);
+/// Set by the timer when the current thread used up its time slice.
+static NEED_RESCHED: AtomicBool = AtomicBool::new(false);
+/// Identifier of the next spawned thread.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Unique identifier of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadId(u64);
+
+impl ThreadId {
+    /// The thread running the kernel entry point.
+    pub const BOOT: Self = Self(0);
+
+    fn new() -> Self {
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for ThreadId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Scheduling state of a thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Currently running.
+    Running,
+    /// Waiting in the ready queue.
+    Ready,
+    /// Waiting for the given tick.
+    Sleeping(u64),
+    /// Waiting for another thread to finish.
+    Joining,
+    /// Done, waiting for its stack to be released.
+    Finished,
+}
+
+/// A kernel thread.
+struct Thread {
+    /// Saved stack pointer, while the thread is not running.
+    stack_pointer: u64,
+    /// Stack of the thread (the boot thread uses the bootloader's stack).
+    #[expect(dead_code, reason = "only kept alive while the thread runs")]
+    stack: Option<Box<[u64]>>,
+    /// Stack the system calls made by the thread run on.
+    syscall_stack: Box<[u64]>,
+    /// Locals of the task the thread was polling when switched out.
+    task_locals: SavedLocals,
+    state: State,
+    /// Threads waiting for this one to finish.
+    joiners: Vec<ThreadId>,
+}
+
+impl Thread {
+    fn new(stack: Option<Box<[u64]>>, state: State) -> Self {
+        Self {
+            stack_pointer: 0,
+            stack,
+            syscall_stack: vec![0; SYSCALL_STACK_WORDS].into_boxed_slice(),
+            task_locals: SavedLocals::NONE,
+            state,
+            joiners: Vec::new(),
+        }
+    }
+
+    /// Top of the stack the system calls of the thread run on, aligned for
+    /// the calls made by the entry point.
+    fn syscall_stack_top(&self) -> u64 {
+        self.syscall_stack.as_ptr_range().end as u64 & !0xF
+    }
+}
+
+/// Round-robin scheduler.
+struct Scheduler {
+    /// Every live thread, boxed so their saved stack pointer does not move.
+    threads: BTreeMap<ThreadId, Box<Thread>>,
+    /// Threads ready to run, in order.
+    ready: VecDeque<ThreadId>,
+    /// Sleeping threads, with their deadline.
+    sleeping: Vec<(u64, ThreadId)>,
+    /// The running thread.
+    current: ThreadId,
+    /// Thread running when no other thread is ready.
+    idle: ThreadId,
+    /// Finished threads, released by the next switch.
+    #[expect(clippy::vec_box, reason = "the saved stack pointer must not move")]
+    finished: Vec<Box<Thread>>,
+}
+
+impl Scheduler {
+    fn current_mut(&mut self) -> &mut Thread {
+        self.threads
+            .get_mut(&self.current)
+            .expect("current thread not registered")
+    }
+
+    /// Marks a thread as ready to run.
+    fn wake(&mut self, id: ThreadId) {
+        if let Some(thread) = self.threads.get_mut(&id) {
+            thread.state = State::Ready;
+            self.ready.push_back(id);
+        }
+    }
+
+    /// Picks the next thread to run, updating the states.
+    ///
+    /// Returns where to save the current stack pointer, and the stack
+    /// pointer to switch to; `None` if the current thread keeps running.
+    fn next(&mut self) -> Option<(*mut u64, u64)> {
+        let now = ticks();
+        let mut due = Vec::new();
+        self.sleeping.retain(|&(deadline, id)| {
+            let expired = deadline <= now;
+            if expired {
+                due.push(id);
+            }
+            !expired
+        });
+        for id in due {
+            self.wake(id);
+        }
+
+        let current = self.current;
+        if self.current_mut().state == State::Running && current != self.idle {
+            self.wake(current);
+        }
+        let next = self.ready.pop_front().unwrap_or(self.idle);
+        let next_thread = self.threads.get_mut(&next)?;
+        next_thread.state = State::Running;
+        syscall::set_kernel_stack(next_thread.syscall_stack_top());
+        let new_stack = next_thread.stack_pointer;
+        let new_locals = next_thread.task_locals;
+        if next == current {
+            return None;
+        }
+
+        self.current = next;
+        let old_thread = self.threads.get_mut(&current)?;
+        old_thread.task_locals = local::switch_current(new_locals);
+        if old_thread.state == State::Finished {
+            let mut finished = self.threads.remove(&current)?;
+            let old_stack = &raw mut finished.stack_pointer;
+            self.finished.push(finished);
+            Some((old_stack, new_stack))
+        } else {
+            Some((&raw mut old_thread.stack_pointer, new_stack))
+        }
+    }
+}
+
+/// Starts the scheduler, turning the running code into the boot thread.
+pub fn init() {
+    let idle = ThreadId::new();
+    let mut threads = BTreeMap::new();
+    let boot = Thread::new(None, State::Running);
+    syscall::set_kernel_stack(boot.syscall_stack_top());
+    threads.insert(ThreadId::BOOT, Box::new(boot));
+    let entry: Entry = Box::new(idle_loop);
+    threads.insert(idle, Box::new(new_thread(entry)));
+    *SCHEDULER.lock() = Some(Scheduler {
+        threads,
+        ready: VecDeque::new(),
+        sleeping: Vec::new(),
+        current: ThreadId::BOOT,
+        idle,
+        finished: Vec::new(),
+    });
+}
+
+/// Runs when no other thread is ready.
+fn idle_loop() {
+    loop {
+        hlt();
+    }
+}
+
+/// Creates a thread, ready to run the given entry point.
+fn new_thread(entry: Entry) -> Thread {
+    let mut stack = vec![0; STACK_WORDS].into_boxed_slice();
+    let entry = Box::into_raw(Box::new(entry)) as u64;
+    let stack_pointer = switch::initial_stack(&mut stack, entry);
+    let mut thread = Thread::new(Some(stack), State::Ready);
+    thread.stack_pointer = stack_pointer;
+    thread
+}
+
+/// Entry point of every new thread.
+///
+/// # Parameters
+/// * `entry` - The boxed entry point of the thread.
+extern "C" fn thread_start(entry: *mut Entry) -> ! {
+    // SAFETY:
+    // The entry point was leaked by `new_thread` for this thread only.
+    let entry = unsafe { Box::from_raw(entry) };
+    // the switch to this thread happened with interrupts disabled
+    interrupts::enable();
+    entry();
+    exit();
+}
+
+/// Terminates the current thread, waking the threads joining it.
+fn exit() -> ! {
+    interrupts::disable();
+    with_scheduler(|scheduler| {
+        let thread = scheduler.current_mut();
+        thread.state = State::Finished;
+        let joiners = mem::take(&mut thread.joiners);
+        for joiner in joiners {
+            scheduler.wake(joiner);
+        }
+    });
+    schedule();
+    panic!("finished thread resumed");
+}
+
+/// Runs a function with the scheduler, if it is initialized.
+fn with_scheduler<R>(function: impl FnOnce(&mut Scheduler) -> R) -> Option<R> {
+    SCHEDULER.lock().as_mut().map(function)
+}
+
+/// Switches to the next thread to run, if any.
+///
+/// Interrupts must be disabled; returns when the current thread is
+/// scheduled again.
+fn schedule() {
+    let switch = with_scheduler(|scheduler| {
+        // not the stacks of the current thread: it is still running
+        scheduler.finished.clear();
+        scheduler.next()
+    });
+    if let Some(Some((old_stack, new_stack))) = switch {
+        // SAFETY:
+        // Both stack pointers are managed by the scheduler, and interrupts
+        // are disabled.
+        unsafe {
+            switch::switch_context(old_stack, new_stack);
+        }
+    }
+}
+
+/// Called by the timer on every tick: the time slice of the current thread
+/// is over.
+pub(crate) fn tick() {
+    NEED_RESCHED.store(true, Ordering::Relaxed);
+}
+
+/// Switches thread if the current one used up its time slice.
+///
+/// Called at the end of the hardware interrupts, with interrupts disabled.
+pub(crate) fn preempt() {
+    if NEED_RESCHED.swap(false, Ordering::Relaxed) {
+        schedule();
+    }
+}
+
+/// Handle to a spawned thread, to wait for its result.
+#[must_use = "dropping the handle detaches the thread"]
+pub struct JoinHandle<T> {
+    id: ThreadId,
+    result: Arc<IrqSpinlock<Option<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Identifier of the thread.
+    #[must_use]
+    pub const fn id(&self) -> ThreadId {
+        self.id
+    }
+
+    /// Waits for the thread to finish, and returns its result.
+    ///
+    /// # Panics
+    /// If the thread panicked (the kernel is halted anyway).
+    #[must_use]
+    pub fn join(self) -> T {
+        interrupts::without_interrupts(|| {
+            let must_wait = with_scheduler(|scheduler| {
+                let current = scheduler.current;
+                let Some(thread) = scheduler.threads.get_mut(&self.id) else {
+                    return false;
+                };
+                if thread.state == State::Finished {
+                    return false;
+                }
+                thread.joiners.push(current);
+                scheduler.current_mut().state = State::Joining;
+                true
+            });
+            if must_wait == Some(true) {
+                schedule();
+            }
+        });
+        self.result
+            .lock()
+            .take()
+            .expect("joined thread did not finish")
+    }
+}
+
+/// Spawns a new kernel thread, running the given function on its own stack.
+///
+/// # Parameters
+/// * `function` - The function run by the thread.
+///
+/// # Panics
+/// If the scheduler is not initialized.
+pub fn spawn_thread<F, T>(function: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let result = Arc::new(IrqSpinlock::new(None));
+    let output = Arc::clone(&result);
+    let entry: Entry = Box::new(move || {
+        let value = function();
+        *output.lock() = Some(value);
+    });
+    let thread = new_thread(entry);
+    let id = ThreadId::new();
+    with_scheduler(|scheduler| {
+        scheduler.threads.insert(id, Box::new(thread));
+        scheduler.ready.push_back(id);
+    })
+    .expect("thread scheduler not initialized");
+    JoinHandle { id, result }
+}
+
+/// Identifier of the running thread.
+#[must_use]
+pub fn current() -> ThreadId {
+    with_scheduler(|scheduler| scheduler.current).unwrap_or(ThreadId::BOOT)
+}
+
+/// Gives the CPU to the next ready thread.
+pub fn yield_now() {
+    interrupts::without_interrupts(schedule);
+}
+
+/// Blocks the current thread for (at least) the given duration.
+///
+/// # Parameters
+/// * `duration` - How long to sleep for.
+pub fn sleep(duration: Duration) {
+    let deadline = ticks() + duration_to_ticks(duration);
+    interrupts::without_interrupts(|| {
+        let scheduled = with_scheduler(|scheduler| {
+            let current = scheduler.current;
+            scheduler.current_mut().state = State::Sleeping(deadline);
+            scheduler.sleeping.push((deadline, current));
+        });
+        if scheduled.is_some() {
+            schedule();
+        }
+    });
+    // without scheduler, or if woken up early
+    while ticks() < deadline {
+        interrupts::without_interrupts(|| {
+            interrupts::enable_and_hlt();
+        });
+    }
+}
+
+/// Threads return their result to the joiner.
+#[test_case]
+fn spawn_and_join() {
+    let handle = spawn_thread(|| 6 * 7);
+    assert_ne!(handle.id(), current(), "same thread");
+    assert_eq!(handle.join(), 42, "wrong result");
+}
+
+/// Each thread makes its system calls on its own stack.
+#[test_case]
+fn syscall_stacks_are_per_thread() {
+    let top = || with_scheduler(|scheduler| scheduler.current_mut().syscall_stack_top());
+    let spawned = spawn_thread(top).join();
+    assert!(top().is_some(), "scheduler not initialized");
+    assert_ne!(spawned, top(), "system call stack shared");
+}
+
+/// A busy thread does not prevent others from running.
+#[test_case]
+fn threads_are_preempted() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let setter = Arc::clone(&flag);
+    let handle = spawn_thread(move || setter.store(true, Ordering::Release));
+    // never yields: only preemption lets the other thread run
+    while !flag.load(Ordering::Acquire) {}
+    let () = handle.join();
+}
+
+/// Sleeping blocks the thread for the duration.
+#[test_case]
+fn sleep_waits() {
+    let start = ticks();
+    let handle = spawn_thread(move || {
+        sleep(Duration::from_millis(30));
+        ticks()
+    });
+    assert!(handle.join() >= start + 3, "woke up too early");
+}
+
+/// The async executor runs inside a thread.
+#[test_case]
+fn executor_in_thread() {
+    use crate::tasks::{simple_executor::SimpleExecutor, timer, Task};
+
+    let flag = Arc::new(AtomicBool::new(false));
+    let setter = Arc::clone(&flag);
+    let handle = spawn_thread(move || {
+        let mut executor = SimpleExecutor::new();
+        executor.spawn(Task::new(async move {
+            timer::sleep(Duration::from_millis(10)).await;
+            setter.store(true, Ordering::Release);
+        }));
+        executor.run();
+    });
+    let () = handle.join();
+    assert!(flag.load(Ordering::Acquire), "task did not run");
+}
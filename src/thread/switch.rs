@@ -0,0 +1,97 @@
+// File: src/thread/switch.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use x86_64::VirtAddr;
+
+use super::thread_start;
+
+/// Saves the callee-saved registers of the current thread on its stack, and
+/// resumes another thread.
+///
+/// # Parameters
+/// * `old_stack` - Where to store the stack pointer of the current thread,
+/// * `new_stack` - Stack pointer of the thread to resume.
+///
+/// # Safety
+/// `new_stack` must have been saved by this function, or prepared by
+/// [`initial_stack`]. Interrupts must be disabled.
+#[unsafe(naked)]
+pub(super) unsafe extern "C" fn switch_context(old_stack: *mut u64, new_stack: u64) {
+    core::arch::naked_asm!(
+        "push rbp",
+        "push rbx",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+        "mov [rdi], rsp",
+        "mov rsp, rsi",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop rbx",
+        "pop rbp",
+        "ret",
+    );
+}
+
+/// First code run by a new thread, with its entry point in `r12`.
+#[unsafe(naked)]
+extern "C" fn thread_trampoline() {
+    core::arch::naked_asm!(
+        "mov rdi, r12",
+        "call {start}",
+        "ud2",
+        start = sym thread_start,
+    );
+}
+
+/// Prepares the stack of a new thread, so that switching to it runs
+/// `thread_start(entry)`.
+///
+/// Returns the initial stack pointer of the thread.
+///
+/// # Parameters
+/// * `stack` - The stack of the thread,
+/// * `entry` - Argument given to the thread entry point.
+pub(super) fn initial_stack(stack: &mut [u64], entry: u64) -> u64 {
+    let trampoline = VirtAddr::from_ptr(thread_trampoline as *const ()).as_u64();
+    // registers popped by `switch_context`, then its return address
+    let frame = [0, 0, 0, entry, 0, 0, trampoline];
+    // the trampoline must run with an aligned stack to call `thread_start`
+    let end = stack.as_ptr_range().end as u64;
+    let top = if end.trailing_zeros() >= 4 {
+        stack.len()
+    } else {
+        stack.len() - 1
+    };
+    let start = top - frame.len();
+    stack[start..top].copy_from_slice(&frame);
+    stack[start..].as_ptr() as u64
+}
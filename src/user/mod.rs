@@ -0,0 +1,271 @@
+// File: src/user/mod.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::{
+    fmt, ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use x86_64::{
+    structures::paging::{
+        mapper::MapToError, FrameAllocator, FrameDeallocator, Mapper, OffsetPageTable, Page,
+        PageSize, PageTableFlags, Size4KiB,
+    },
+    VirtAddr,
+};
+
+use crate::{interrupts::gdt, paging::with_memory, BootInfoFrameAllocator};
+
+/// Switching between kernel and user mode.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+mod switch;
+
+/// Address the programs are loaded at.
+pub const CODE_START: u64 = 0x0000_0800_0000_0000;
+/// Top of the stack of the programs.
+pub const STACK_TOP: u64 = 0x0000_0800_4000_0000;
+/// Number of pages of the stack of the programs.
+pub const STACK_PAGES: u64 = 4;
+/// Maximum size of a program.
+pub const MAX_PROGRAM_SIZE: usize = 64 * 1024;
+
+/// Exit kind: the program called the `exit` system call.
+const EXITED: u64 = 0;
+/// Exit kind: the program caused a CPU exception.
+const FAULTED: u64 = 1;
+
+/// Whether a program is currently running.
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// How a user program ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The program exited with the given status.
+    Exited(u64),
+    /// The program was killed by the given CPU exception.
+    Faulted(u8),
+}
+
+/// Errors preventing a program from running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserError {
+    /// The program is empty or larger than [`MAX_PROGRAM_SIZE`].
+    InvalidSize,
+    /// The kernel memory management is not initialized.
+    NotInitialized,
+    /// Another program is already running.
+    Busy,
+    /// No memory left to load the program.
+    OutOfMemory,
+    /// The addresses of the program are already in use.
+    AddressInUse,
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize => write!(f, "invalid program size"),
+            Self::NotInitialized => write!(f, "memory management not initialized"),
+            Self::Busy => write!(f, "a program is already running"),
+            Self::OutOfMemory => write!(f, "out of memory"),
+            Self::AddressInUse => write!(f, "program addresses already in use"),
+        }
+    }
+}
+
+impl From<MapToError<Size4KiB>> for UserError {
+    fn from(error: MapToError<Size4KiB>) -> Self {
+        match error {
+            MapToError::FrameAllocationFailed => Self::OutOfMemory,
+            MapToError::ParentEntryHugePage | MapToError::PageAlreadyMapped(_) => {
+                Self::AddressInUse
+            }
+        }
+    }
+}
+
+/// Runs a position independent program in user mode, until it exits.
+///
+/// The program is copied at [`CODE_START`] and starts at its first byte, with
+/// a fresh stack below [`STACK_TOP`]. It talks to the kernel through system
+/// calls; if it faults, the fault is reported and the program killed.
+///
+/// # Parameters
+/// * `program` - Machine code of the program.
+///
+/// # Errors
+/// If the program could not be started.
+pub fn run(program: &[u8]) -> Result<ExitStatus, UserError> {
+    if program.is_empty() || program.len() > MAX_PROGRAM_SIZE {
+        return Err(UserError::InvalidSize);
+    }
+    if RUNNING.swap(true, Ordering::Acquire) {
+        return Err(UserError::Busy);
+    }
+
+    let mapped = with_memory(|mapper, frames| load(mapper, frames, program))
+        .ok_or(UserError::NotInitialized)
+        .and_then(|result| result.map_err(UserError::from));
+    let status = mapped.map(|()| {
+        let selectors = gdt::selectors();
+        // SAFETY:
+        // The program and its stack were just mapped, and no other program
+        // is running.
+        let exit = unsafe {
+            switch::enter_user(
+                CODE_START,
+                STACK_TOP,
+                u64::from(selectors.user_code.0),
+                u64::from(selectors.user_data.0),
+            )
+        };
+        if exit.kind == EXITED {
+            ExitStatus::Exited(exit.value)
+        } else {
+            #[expect(clippy::cast_possible_truncation)]
+            ExitStatus::Faulted(exit.value as u8)
+        }
+    });
+
+    with_memory(|mapper, frames| unload(mapper, frames, program.len()));
+    RUNNING.store(false, Ordering::Release);
+    status
+}
+
+/// Terminates the running program, returning to the kernel.
+///
+/// Returns only if no program is running.
+///
+/// # Parameters
+/// * `status` - The exit status.
+pub(crate) fn exit(status: u64) {
+    if RUNNING.load(Ordering::Acquire) {
+        // SAFETY:
+        // A program is running.
+        unsafe { switch::return_to_kernel(EXITED, status) }
+    }
+}
+
+/// Kills the running program after a CPU exception, returning to the kernel.
+///
+/// Returns only if no program is running.
+///
+/// # Parameters
+/// * `vector` - The exception vector.
+pub(crate) fn kill(vector: u8) {
+    if RUNNING.load(Ordering::Acquire) {
+        // SAFETY:
+        // A program is running.
+        unsafe { switch::return_to_kernel(FAULTED, u64::from(vector)) }
+    }
+}
+
+/// Pages holding a program of the given length.
+fn code_pages(len: usize) -> impl Iterator<Item = Page> {
+    let start = Page::containing_address(VirtAddr::new(CODE_START));
+    (0..(len as u64).div_ceil(Size4KiB::SIZE)).map(move |index| start + index)
+}
+
+/// Pages of the stack of the programs.
+fn stack_pages() -> impl Iterator<Item = Page> {
+    let top = Page::<Size4KiB>::containing_address(VirtAddr::new(STACK_TOP));
+    (1..=STACK_PAGES).map(move |index| top - index)
+}
+
+/// Maps a page to a new frame filled with the given bytes.
+///
+/// # Parameters
+/// * `mapper` - The page tables,
+/// * `frames` - The frame allocator,
+/// * `page` - The page to map,
+/// * `bytes` - The content of the page (zero filled),
+/// * `flags` - Flags of the mapping.
+fn map_page(
+    mapper: &mut OffsetPageTable<'static>,
+    frames: &mut BootInfoFrameAllocator,
+    page: Page,
+    bytes: &[u8],
+    flags: PageTableFlags,
+) -> Result<(), MapToError<Size4KiB>> {
+    let frame = frames
+        .allocate_frame()
+        .ok_or(MapToError::FrameAllocationFailed)?;
+    let content: *mut u8 = (mapper.phys_offset() + frame.start_address().as_u64()).as_mut_ptr();
+    // SAFETY:
+    // The frame was just allocated, and is mapped at the physical memory
+    // offset.
+    unsafe {
+        ptr::write_bytes(content, 0, 4096);
+        ptr::copy_nonoverlapping(bytes.as_ptr(), content, bytes.len().min(4096));
+    }
+    let table_flags =
+        PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+    // SAFETY:
+    // The frame is unused, and the user pages are only used by programs.
+    unsafe {
+        mapper
+            .map_to_with_table_flags(page, frame, flags, table_flags, frames)?
+            .flush();
+    }
+    Ok(())
+}
+
+/// Maps the program and its stack.
+fn load(
+    mapper: &mut OffsetPageTable<'static>,
+    frames: &mut BootInfoFrameAllocator,
+    program: &[u8],
+) -> Result<(), MapToError<Size4KiB>> {
+    let code_flags = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+    for (page, bytes) in code_pages(program.len()).zip(program.chunks(4096)) {
+        map_page(mapper, frames, page, bytes, code_flags)?;
+    }
+    let stack_flags = code_flags | PageTableFlags::WRITABLE;
+    for page in stack_pages() {
+        map_page(mapper, frames, page, &[], stack_flags)?;
+    }
+    Ok(())
+}
+
+/// Unmaps the program and its stack, releasing their frames.
+fn unload(
+    mapper: &mut OffsetPageTable<'static>,
+    frames: &mut BootInfoFrameAllocator,
+    program_len: usize,
+) {
+    for page in code_pages(program_len).chain(stack_pages()) {
+        if let Ok((frame, flush)) = mapper.unmap(page) {
+            flush.flush();
+            // SAFETY:
+            // The frame was only used by the program.
+            unsafe {
+                frames.deallocate_frame(frame);
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// File: src/user/switch.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use core::sync::atomic::AtomicU64;
+
+/// Kernel stack pointer saved when entering user mode.
+static KERNEL_STACK: AtomicU64 = AtomicU64::new(0);
+
+/// Outcome of a user mode program, as returned to [`enter_user`].
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub(super) struct RawExit {
+    /// Kind of exit (see [`super::ExitStatus`]).
+    pub(super) kind: u64,
+    /// Exit status, or faulting vector.
+    pub(super) value: u64,
+}
+
+/// Switches to user mode, until the program exits or faults.
+///
+/// The callee-saved registers and flags are saved on the current stack, and
+/// the stack pointer in a static, so that [`return_to_kernel`] can resume
+/// here from any kernel stack.
+///
+/// # Parameters
+/// * `entry` - Address of the first instruction to run,
+/// * `stack` - Top of the user stack,
+/// * `code_selector` - User code segment,
+/// * `data_selector` - User data segment.
+///
+/// # Safety
+/// The code and stack must be mapped and accessible from user mode, and no
+/// other program may be running.
+#[unsafe(naked)]
+pub(super) unsafe extern "C" fn enter_user(
+    entry: u64,
+    stack: u64,
+    code_selector: u64,
+    data_selector: u64,
+) -> RawExit {
+    core::arch::naked_asm!(
+        "push rbx",
+        "push rbp",
+        "push r12",
+        "push r13",
+        "push r14",
+        "push r15",
+        "pushfq",
+        "mov [rip + {kernel_stack}], rsp",
+        // interrupt frame of the user program, interrupts enabled
+        "push rcx",
+        "push rsi",
+        "push 0x202",
+        "push rdx",
+        "push rdi",
+        // do not leak kernel values to the program
+        "xor eax, eax",
+        "xor ebx, ebx",
+        "xor ecx, ecx",
+        "xor edx, edx",
+        "xor esi, esi",
+        "xor edi, edi",
+        "xor ebp, ebp",
+        "xor r8d, r8d",
+        "xor r9d, r9d",
+        "xor r10d, r10d",
+        "xor r11d, r11d",
+        "xor r12d, r12d",
+        "xor r13d, r13d",
+        "xor r14d, r14d",
+        "xor r15d, r15d",
+        "iretq",
+        kernel_stack = sym KERNEL_STACK,
+    );
+}
+
+/// Resumes the kernel where [`enter_user`] was called.
+///
+/// The stack the kernel is currently running on (system call or interrupt
+/// stack) is abandoned.
+///
+/// # Parameters
+/// * `kind` - Kind of exit,
+/// * `value` - Exit status, or faulting vector.
+///
+/// # Safety
+/// A program must have been started with [`enter_user`].
+#[unsafe(naked)]
+pub(super) unsafe extern "C" fn return_to_kernel(kind: u64, value: u64) -> ! {
+    core::arch::naked_asm!(
+        "mov rsp, [rip + {kernel_stack}]",
+        "mov rax, rdi",
+        "mov rdx, rsi",
+        "popfq",
+        "pop r15",
+        "pop r14",
+        "pop r13",
+        "pop r12",
+        "pop rbp",
+        "pop rbx",
+        "ret",
+        kernel_stack = sym KERNEL_STACK,
+    );
+}
@@ -0,0 +1,85 @@
+// File: tests/async_tasks.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crysalis::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use bootloader::{entry_point, BootInfo};
+use core::{panic::PanicInfo, time::Duration};
+use crysalis::{
+    async_test, hlt_loop,
+    tasks::{sync::mpsc, timer},
+};
+use futures_util::future::join;
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    crysalis::init(boot_info);
+
+    test_main();
+    hlt_loop();
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    crysalis::test_panic_handler(info)
+}
+
+async_test! {
+    timeout = Duration::from_millis(500);
+    /// Sleeping completes within the test timeout.
+    async fn sleep_completes() {
+        timer::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+async_test! {
+    timeout = Duration::from_secs(1);
+    /// Values go through a bounded channel between a producer and a consumer.
+    async fn channel_between_futures() {
+        let (sender, mut receiver) = mpsc::channel(2);
+        let producer = async move {
+            for value in 0..8 {
+                assert!(sender.send(value).await.is_ok(), "receiver dropped");
+            }
+        };
+        let consumer = async move {
+            let mut sum = 0;
+            while let Some(value) = receiver.recv().await {
+                sum += value;
+            }
+            sum
+        };
+        let ((), sum) = join(producer, consumer).await;
+        assert_eq!(sum, 28, "values lost");
+    }
+}
@@ -0,0 +1,151 @@
+// File: tests/user_mode.rs
+// Project: Crysalis OS
+// Creation date: Sunday 18 October 2026
+// Author: Vincent Berthier <test.test>
+// -----
+// Last modified: Sunday 18 October 2026 @ 17:30:00
+// Modified by: Vincent Berthier
+// -----
+// Copyright (c) 2024 <Vincent Berthier>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the 'Software'), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED 'AS IS', WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(crysalis::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+use bootloader::{entry_point, BootInfo};
+use core::{panic::PanicInfo, slice};
+use crysalis::{
+    hlt_loop,
+    syscall::SyscallError,
+    user::{self, ExitStatus},
+};
+use x86_64::structures::idt::ExceptionVector;
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    crysalis::init(boot_info);
+
+    test_main();
+    hlt_loop();
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    crysalis::test_panic_handler(info)
+}
+
+/// Tiny position independent user programs.
+#[expect(clippy::inline_asm_x86_intel_syntax)]
+mod programs {
+    use core::arch::global_asm;
+
+    global_asm!(
+        ".section .rodata.user_programs, \"a\"",
+        // writes a message on the serial interface, then exits with status 42
+        ".global USER_HELLO_START",
+        "USER_HELLO_START:",
+        "lea rsi, [rip + 2f]",
+        "lea rdx, [rip + 3f]",
+        "sub rdx, rsi",
+        "mov edi, 2",
+        "xor eax, eax",
+        "syscall",
+        "mov edi, 42",
+        "mov eax, 1",
+        "syscall",
+        "ud2",
+        "2: .ascii \"hello from user mode\\n\"",
+        "3:",
+        ".global USER_HELLO_END",
+        "USER_HELLO_END:",
+        // privileged instruction
+        ".global USER_FAULT_START",
+        "USER_FAULT_START:",
+        "hlt",
+        ".global USER_FAULT_END",
+        "USER_FAULT_END:",
+        // tries to print kernel memory
+        ".global USER_SNOOP_START",
+        "USER_SNOOP_START:",
+        "movabs rsi, offset USER_HELLO_START",
+        "mov edx, 16",
+        "mov edi, 2",
+        "xor eax, eax",
+        "syscall",
+        "mov rdi, rax",
+        "mov eax, 1",
+        "syscall",
+        ".global USER_SNOOP_END",
+        "USER_SNOOP_END:",
+        ".previous",
+    );
+}
+
+extern "C" {
+    static USER_HELLO_START: u8;
+    static USER_HELLO_END: u8;
+    static USER_FAULT_START: u8;
+    static USER_FAULT_END: u8;
+    static USER_SNOOP_START: u8;
+    static USER_SNOOP_END: u8;
+}
+
+/// Machine code between two symbols.
+fn program(start: *const u8, end: *const u8) -> &'static [u8] {
+    // SAFETY:
+    // Both symbols delimit the same program.
+    unsafe { slice::from_raw_parts(start, end.offset_from(start).unsigned_abs()) }
+}
+
+#[test_case]
+fn syscall_from_user_mode() {
+    let hello = program(&raw const USER_HELLO_START, &raw const USER_HELLO_END);
+    assert_eq!(user::run(hello), Ok(ExitStatus::Exited(42)));
+}
+
+#[test_case]
+fn user_fault_does_not_crash_the_kernel() {
+    let fault = program(&raw const USER_FAULT_START, &raw const USER_FAULT_END);
+    assert_eq!(
+        user::run(fault),
+        Ok(ExitStatus::Faulted(
+            ExceptionVector::GeneralProtection as u8
+        ))
+    );
+    // the kernel can still run programs
+    let hello = program(&raw const USER_HELLO_START, &raw const USER_HELLO_END);
+    assert_eq!(user::run(hello), Ok(ExitStatus::Exited(42)));
+}
+
+#[test_case]
+fn kernel_memory_is_not_readable() {
+    let snoop = program(&raw const USER_SNOOP_START, &raw const USER_SNOOP_END);
+    // the write fails with a bad address error, returned as exit status
+    assert_eq!(
+        user::run(snoop),
+        Ok(ExitStatus::Exited(
+            SyscallError::BadAddress.as_return_value()
+        ))
+    );
+}